@@ -1,5 +1,6 @@
 use std::path::Path;
-use std::sync::Mutex;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
 
 use tauri::State;
 
@@ -10,6 +11,8 @@ use crate::services::file_service::FileService;
 /// Shared application state holding the currently opened repository path.
 pub struct AppState {
     pub repo_path: Mutex<Option<String>>,
+    /// Set to abort the running clone, fetch, pull or push.
+    pub cancel_transfer: Arc<AtomicBool>,
 }
 
 /// Helper to get the FileService from the current AppState.
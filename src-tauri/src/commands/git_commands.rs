@@ -1,10 +1,18 @@
 use std::path::Path;
+use std::sync::atomic::Ordering;
 
-use tauri::State;
+use tauri::{Emitter, State};
 
 use crate::commands::file_commands::AppState;
+use crate::commands::repo_commands::TRANSFER_PROGRESS_EVENT;
+use crate::models::diff::{CellRef, FileDiff};
 use crate::models::error::AppError;
-use crate::models::git::{BranchList, Commit, MergeResult, PullResult, Remote, RepoStatus};
+use crate::models::git::{
+    BranchList, BundleInfo, CheckoutResult, Commit, CommitDetails, ConflictList, FetchResult,
+    LogOptions, MergePreview, MergeResult, MergeStrategyRule, PullResult, PushResult, RebaseResult,
+    ReflogEntry, Remote, RemoteAuth, ReplayResult, RepoState, RepoStatus, ResetMode, ResetResult,
+    RestoreResult, StashEntry, TagInfo,
+};
 use crate::services::git_service::GitService;
 
 /// Helper to get the GitService from the current AppState.
@@ -21,29 +29,84 @@ pub fn git_status(state: State<AppState>) -> Result<RepoStatus, AppError> {
     service.status()
 }
 
-/// Stage files and create a commit.
+/// Stage files and create a commit. With `amend`, replace HEAD instead; an
+/// empty message then keeps HEAD's message.
 #[tauri::command]
 pub fn git_commit(
     state: State<AppState>,
     message: String,
     files: Vec<String>,
+    amend: Option<bool>,
 ) -> Result<Commit, AppError> {
     let service = get_git_service(&state)?;
-    service.commit(&message, &files)
+    if amend.unwrap_or(false) {
+        service.amend(&message, &files)
+    } else {
+        service.commit(&message, &files)
+    }
 }
 
-/// Get the commit log with optional file filter and pagination.
+/// Reset the current branch to `target`. A hard reset also needs `confirm`
+/// set to the full hash of the current HEAD.
+#[tauri::command]
+pub fn git_reset(
+    state: State<AppState>,
+    target: String,
+    mode: ResetMode,
+    confirm: Option<String>,
+) -> Result<ResetResult, AppError> {
+    let service = get_git_service(&state)?;
+    service.reset(&target, mode, confirm.as_deref())
+}
+
+/// List a ref's reflog (default `HEAD`), newest first, with a table-diff
+/// summary per entry.
+#[tauri::command]
+pub fn git_reflog(
+    state: State<AppState>,
+    reference: Option<String>,
+) -> Result<Vec<ReflogEntry>, AppError> {
+    let service = get_git_service(&state)?;
+    service.reflog(reference.as_deref().unwrap_or("HEAD"))
+}
+
+/// Create a branch at the commit a reflog entry points to.
+#[tauri::command]
+pub fn git_reflog_recover(
+    state: State<AppState>,
+    reference: Option<String>,
+    index: usize,
+    branch: String,
+) -> Result<Commit, AppError> {
+    let service = get_git_service(&state)?;
+    service.recover_reflog_entry(reference.as_deref().unwrap_or("HEAD"), index, &branch)
+}
+
+/// Get the commit log with optional file filter and pagination. `options`
+/// selects the refs to walk (default HEAD) and topological ordering.
 #[tauri::command]
 pub fn git_log(
     state: State<AppState>,
     file: Option<String>,
     limit: Option<usize>,
     offset: Option<usize>,
+    options: Option<LogOptions>,
 ) -> Result<Vec<Commit>, AppError> {
     let service = get_git_service(&state)?;
     let limit = limit.unwrap_or(50);
     let offset = offset.unwrap_or(0);
-    service.log(file.as_deref(), limit, offset)
+    service.log(file.as_deref(), limit, offset, &options.unwrap_or_default())
+}
+
+/// Full details of one commit: message body and trailers, identities with
+/// their timezones, and per-file row and cell counts.
+#[tauri::command]
+pub fn git_commit_details(
+    state: State<AppState>,
+    hash: String,
+) -> Result<CommitDetails, AppError> {
+    let service = get_git_service(&state)?;
+    service.commit_details(&hash)
 }
 
 /// Show the contents of a file at a specific commit.
@@ -57,6 +120,44 @@ pub fn git_show_file(
     service.show_file(&hash, &file_path)
 }
 
+/// Restore a file to its content at a commit, optionally staging it. Reports
+/// uncommitted changes to the file instead of overwriting them unless `force`.
+#[tauri::command]
+pub fn git_restore_file(
+    state: State<AppState>,
+    file_path: String,
+    hash: String,
+    stage: Option<bool>,
+    force: Option<bool>,
+) -> Result<RestoreResult, AppError> {
+    let service = get_git_service(&state)?;
+    service.restore_file(
+        &file_path,
+        &hash,
+        stage.unwrap_or(false),
+        force.unwrap_or(false),
+    )
+}
+
+/// Copy selected rows and cells of a file from another commit or branch into
+/// the working file, returning the diff of what was applied.
+#[tauri::command]
+pub fn git_pick_cells(
+    state: State<AppState>,
+    source: String,
+    file_path: String,
+    rows: Option<Vec<String>>,
+    cells: Option<Vec<CellRef>>,
+) -> Result<FileDiff, AppError> {
+    let service = get_git_service(&state)?;
+    service.pick_cells(
+        &source,
+        &file_path,
+        &rows.unwrap_or_default(),
+        &cells.unwrap_or_default(),
+    )
+}
+
 /// List all branches.
 #[tauri::command]
 pub fn git_branches(state: State<AppState>) -> Result<BranchList, AppError> {
@@ -75,14 +176,86 @@ pub fn git_create_branch(
     service.create_branch(&name, from.as_deref())
 }
 
-/// Checkout an existing branch.
+/// Delete a local branch. Unmerged branches require `force`.
 #[tauri::command]
-pub fn git_checkout(
+pub fn git_delete_branch(
     state: State<AppState>,
-    branch: String,
+    name: String,
+    force: Option<bool>,
 ) -> Result<(), AppError> {
     let service = get_git_service(&state)?;
-    service.checkout(&branch)
+    service.delete_branch(&name, force.unwrap_or(false))
+}
+
+/// Rename a local branch.
+#[tauri::command]
+pub fn git_rename_branch(
+    state: State<AppState>,
+    name: String,
+    new_name: String,
+) -> Result<(), AppError> {
+    let service = get_git_service(&state)?;
+    service.rename_branch(&name, &new_name)
+}
+
+/// Create a local branch tracking a remote-tracking branch. Returns the local name.
+#[tauri::command]
+pub fn git_track_branch(
+    state: State<AppState>,
+    remote_branch: String,
+    name: Option<String>,
+) -> Result<String, AppError> {
+    let service = get_git_service(&state)?;
+    service.track_branch(&remote_branch, name.as_deref())
+}
+
+/// Set or (with no `upstream`) unset a branch's upstream.
+#[tauri::command]
+pub fn git_set_upstream(
+    state: State<AppState>,
+    name: String,
+    upstream: Option<String>,
+) -> Result<(), AppError> {
+    let service = get_git_service(&state)?;
+    service.set_upstream(&name, upstream.as_deref())
+}
+
+/// List tags with the commits they point to.
+#[tauri::command]
+pub fn git_tags(state: State<AppState>) -> Result<Vec<TagInfo>, AppError> {
+    let service = get_git_service(&state)?;
+    service.tags()
+}
+
+/// Create an annotated tag at `target`, or HEAD.
+#[tauri::command]
+pub fn git_create_tag(
+    state: State<AppState>,
+    name: String,
+    target: Option<String>,
+    message: String,
+) -> Result<TagInfo, AppError> {
+    let service = get_git_service(&state)?;
+    service.create_tag(&name, target.as_deref(), &message)
+}
+
+/// Delete a local tag.
+#[tauri::command]
+pub fn git_delete_tag(state: State<AppState>, name: String) -> Result<(), AppError> {
+    let service = get_git_service(&state)?;
+    service.delete_tag(&name)
+}
+
+/// Checkout an existing branch. Refuses when uncommitted changes would be
+/// overwritten, unless `auto_stash` is set.
+#[tauri::command]
+pub fn git_checkout(
+    state: State<AppState>,
+    branch: String,
+    auto_stash: Option<bool>,
+) -> Result<CheckoutResult, AppError> {
+    let mut service = get_git_service(&state)?;
+    service.checkout(&branch, auto_stash.unwrap_or(false))
 }
 
 /// Merge a source branch into the current branch.
@@ -95,42 +268,196 @@ pub fn git_merge(
     service.merge(&source)
 }
 
-/// Push to a remote.
+/// Preview merging a source branch into the current branch without touching the working tree.
 #[tauri::command]
-pub fn git_push(
+pub fn git_merge_preview(
     state: State<AppState>,
+    source: String,
+) -> Result<MergePreview, AppError> {
+    let service = get_git_service(&state)?;
+    service.merge_preview(&source)
+}
+
+/// Push a branch to a remote, emitting progress events. `force_with_lease`
+/// is the commit the remote branch is expected to be at; when given, the
+/// push is forced if and only if that still holds.
+#[tauri::command]
+pub async fn git_push(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
     remote: Option<String>,
     branch: Option<String>,
-) -> Result<(), AppError> {
+    force_with_lease: Option<String>,
+    auth: Option<RemoteAuth>,
+) -> Result<PushResult, AppError> {
+    let path = state.repo_path.lock().unwrap().clone().ok_or(AppError::NoRepo)?;
+    let cancel = state.cancel_transfer.clone();
+    cancel.store(false, Ordering::Relaxed);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let service = GitService::open(Path::new(&path))?;
+        let remote_name = remote.as_deref().unwrap_or("origin");
+        let branch_name = match branch {
+            Some(b) => b,
+            None => {
+                let status = service.status()?;
+                status.branch
+            }
+        };
+        let mut emit = |progress| {
+            let _ = app.emit(TRANSFER_PROGRESS_EVENT, progress);
+        };
+        service.push(
+            remote_name,
+            &branch_name,
+            force_with_lease.as_deref(),
+            &auth.unwrap_or_default(),
+            &cancel,
+            &mut emit,
+        )
+    })
+    .await
+    .map_err(|e| AppError::IoError(std::io::Error::other(e.to_string())))?
+}
+
+/// Push a tag to a remote, emitting progress events.
+#[tauri::command]
+pub async fn git_push_tag(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    remote: Option<String>,
+    name: String,
+    auth: Option<RemoteAuth>,
+) -> Result<PushResult, AppError> {
+    let path = state.repo_path.lock().unwrap().clone().ok_or(AppError::NoRepo)?;
+    let cancel = state.cancel_transfer.clone();
+    cancel.store(false, Ordering::Relaxed);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let service = GitService::open(Path::new(&path))?;
+        let mut emit = |progress| {
+            let _ = app.emit(TRANSFER_PROGRESS_EVENT, progress);
+        };
+        service.push_tag(
+            remote.as_deref().unwrap_or("origin"),
+            &name,
+            &auth.unwrap_or_default(),
+            &cancel,
+            &mut emit,
+        )
+    })
+    .await
+    .map_err(|e| AppError::IoError(std::io::Error::other(e.to_string())))?
+}
+
+/// Write a bundle file of `branches` (default all) for offline sharing,
+/// optionally only with the commits after `since`.
+#[tauri::command]
+pub fn git_export_bundle(
+    state: State<AppState>,
+    dest: String,
+    branches: Option<Vec<String>>,
+    since: Option<String>,
+) -> Result<BundleInfo, AppError> {
     let service = get_git_service(&state)?;
-    let remote_name = remote.as_deref().unwrap_or("origin");
-    let branch_name = match branch {
-        Some(b) => b,
-        None => {
-            let status = service.status()?;
-            status.branch
-        }
-    };
-    service.push(remote_name, &branch_name)
+    service.export_bundle(
+        Path::new(&dest),
+        &branches.unwrap_or_default(),
+        since.as_deref(),
+    )
 }
 
-/// Pull from a remote (fetch + merge).
+/// Fetch a bundle file's branches as remote-tracking branches of `remote`
+/// (default `bundle`), ready for `git_merge`.
 #[tauri::command]
-pub fn git_pull(
+pub fn git_fetch_bundle(
     state: State<AppState>,
+    path: String,
+    remote: Option<String>,
+) -> Result<FetchResult, AppError> {
+    let service = get_git_service(&state)?;
+    service.fetch_bundle(Path::new(&path), remote.as_deref().unwrap_or("bundle"))
+}
+
+/// Pull from a remote (fetch + merge, or fetch + rebase with `rebase`),
+/// emitting progress events for the fetch. Cancel with `repo_cancel_transfer`.
+#[tauri::command]
+pub async fn git_pull(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
     remote: Option<String>,
     branch: Option<String>,
+    rebase: Option<bool>,
+    auth: Option<RemoteAuth>,
 ) -> Result<PullResult, AppError> {
+    let path = state.repo_path.lock().unwrap().clone().ok_or(AppError::NoRepo)?;
+    let cancel = state.cancel_transfer.clone();
+    cancel.store(false, Ordering::Relaxed);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let service = GitService::open(Path::new(&path))?;
+        let remote_name = remote.as_deref().unwrap_or("origin");
+        let branch_name = match branch {
+            Some(b) => b,
+            None => {
+                let status = service.status()?;
+                status.branch
+            }
+        };
+        let mut emit = |progress| {
+            let _ = app.emit(TRANSFER_PROGRESS_EVENT, progress);
+        };
+        service.pull(
+            remote_name,
+            &branch_name,
+            rebase.unwrap_or(false),
+            &auth.unwrap_or_default(),
+            &cancel,
+            &mut emit,
+        )
+    })
+    .await
+    .map_err(|e| AppError::IoError(std::io::Error::other(e.to_string())))?
+}
+
+/// Stage the files resolved during a stopped rebase and continue replaying.
+#[tauri::command]
+pub fn git_rebase_continue(
+    state: State<AppState>,
+    files: Vec<String>,
+) -> Result<RebaseResult, AppError> {
+    let service = get_git_service(&state)?;
+    service.rebase_continue(&files)
+}
+
+/// Abort an in-progress rebase, restoring the branch.
+#[tauri::command]
+pub fn git_rebase_abort(state: State<AppState>) -> Result<(), AppError> {
+    let service = get_git_service(&state)?;
+    service.rebase_abort()
+}
+
+/// Revert a commit, or only `file`'s changes from it, as a new commit.
+#[tauri::command]
+pub fn git_revert(
+    state: State<AppState>,
+    hash: String,
+    file: Option<String>,
+) -> Result<ReplayResult, AppError> {
     let service = get_git_service(&state)?;
-    let remote_name = remote.as_deref().unwrap_or("origin");
-    let branch_name = match branch {
-        Some(b) => b,
-        None => {
-            let status = service.status()?;
-            status.branch
-        }
-    };
-    service.pull(remote_name, &branch_name)
+    service.revert(&hash, file.as_deref())
+}
+
+/// Cherry-pick a commit onto HEAD, or only the given `files` from it.
+/// Conflicts are finished with `git_resolve_conflicts` or `git_merge_abort`.
+#[tauri::command]
+pub fn git_cherry_pick(
+    state: State<AppState>,
+    hash: String,
+    files: Option<Vec<String>>,
+) -> Result<ReplayResult, AppError> {
+    let service = get_git_service(&state)?;
+    service.cherry_pick(&hash, &files.unwrap_or_default())
 }
 
 /// List all remotes.
@@ -140,6 +467,35 @@ pub fn git_remotes(state: State<AppState>) -> Result<Vec<Remote>, AppError> {
     service.remotes()
 }
 
+/// Fetch from a remote without merging, emitting progress events.
+/// Cancel with `repo_cancel_transfer`.
+#[tauri::command]
+pub async fn git_fetch(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    remote: Option<String>,
+    auth: Option<RemoteAuth>,
+) -> Result<FetchResult, AppError> {
+    let path = state.repo_path.lock().unwrap().clone().ok_or(AppError::NoRepo)?;
+    let cancel = state.cancel_transfer.clone();
+    cancel.store(false, Ordering::Relaxed);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let service = GitService::open(Path::new(&path))?;
+        let mut emit = |progress| {
+            let _ = app.emit(TRANSFER_PROGRESS_EVENT, progress);
+        };
+        service.fetch(
+            remote.as_deref(),
+            &auth.unwrap_or_default(),
+            &cancel,
+            &mut emit,
+        )
+    })
+    .await
+    .map_err(|e| AppError::IoError(std::io::Error::other(e.to_string())))?
+}
+
 /// Add a remote to the repository.
 #[tauri::command]
 pub fn git_add_remote(
@@ -151,6 +507,46 @@ pub fn git_add_remote(
     service.add_remote(&name, &url)
 }
 
+/// Remove a remote and its remote-tracking branches.
+#[tauri::command]
+pub fn git_remove_remote(state: State<AppState>, name: String) -> Result<(), AppError> {
+    let service = get_git_service(&state)?;
+    service.remove_remote(&name)
+}
+
+/// Rename a remote. Returns refspecs that could not be renamed automatically.
+#[tauri::command]
+pub fn git_rename_remote(
+    state: State<AppState>,
+    name: String,
+    new_name: String,
+) -> Result<Vec<String>, AppError> {
+    let service = get_git_service(&state)?;
+    service.rename_remote(&name, &new_name)
+}
+
+/// Set a remote's fetch URL.
+#[tauri::command]
+pub fn git_set_remote_url(
+    state: State<AppState>,
+    name: String,
+    url: String,
+) -> Result<(), AppError> {
+    let service = get_git_service(&state)?;
+    service.set_remote_url(&name, &url)
+}
+
+/// Set a remote's push URLs; an empty list reverts to pushing to the fetch URL.
+#[tauri::command]
+pub fn git_set_remote_push_urls(
+    state: State<AppState>,
+    name: String,
+    urls: Vec<String>,
+) -> Result<(), AppError> {
+    let service = get_git_service(&state)?;
+    service.set_remote_push_urls(&name, &urls)
+}
+
 /// Resolve merge conflicts by staging the resolved files.
 #[tauri::command]
 pub fn git_resolve_conflicts(
@@ -160,3 +556,96 @@ pub fn git_resolve_conflicts(
     let service = get_git_service(&state)?;
     service.resolve_conflicts(&files)
 }
+
+/// List conflicted files with their ancestor/ours/theirs contents.
+#[tauri::command]
+pub fn git_conflicts(state: State<AppState>) -> Result<ConflictList, AppError> {
+    let service = get_git_service(&state)?;
+    service.conflicts()
+}
+
+/// Abort an in-progress merge and restore the pre-merge working tree.
+#[tauri::command]
+pub fn git_merge_abort(state: State<AppState>) -> Result<(), AppError> {
+    let service = get_git_service(&state)?;
+    service.merge_abort()
+}
+
+/// Report whether a merge, revert, cherry-pick or rebase is in progress.
+#[tauri::command]
+pub fn git_repo_state(state: State<AppState>) -> Result<RepoState, AppError> {
+    let service = get_git_service(&state)?;
+    service.repo_state()
+}
+
+/// List the per-file merge strategy rules.
+#[tauri::command]
+pub fn git_merge_strategies(state: State<AppState>) -> Result<Vec<MergeStrategyRule>, AppError> {
+    let service = get_git_service(&state)?;
+    service.merge_strategies()
+}
+
+/// Add or replace the merge strategy rule for a file pattern.
+#[tauri::command]
+pub fn git_set_merge_strategy(
+    state: State<AppState>,
+    rule: MergeStrategyRule,
+) -> Result<(), AppError> {
+    let service = get_git_service(&state)?;
+    service.set_merge_strategy(&rule)
+}
+
+/// Remove the merge strategy rule for a file pattern.
+#[tauri::command]
+pub fn git_remove_merge_strategy(
+    state: State<AppState>,
+    pattern: String,
+) -> Result<(), AppError> {
+    let service = get_git_service(&state)?;
+    service.remove_merge_strategy(&pattern)
+}
+
+/// Stash uncommitted changes, optionally only the given files.
+#[tauri::command]
+pub fn git_stash_save(
+    state: State<AppState>,
+    message: String,
+    files: Option<Vec<String>>,
+) -> Result<StashEntry, AppError> {
+    let mut service = get_git_service(&state)?;
+    service.stash_save(&message, &files.unwrap_or_default())
+}
+
+/// List stash entries with a table-diff summary of each.
+#[tauri::command]
+pub fn git_stash_list(state: State<AppState>) -> Result<Vec<StashEntry>, AppError> {
+    let service = get_git_service(&state)?;
+    service.stash_list()
+}
+
+/// Apply a stash entry (default: the latest) and keep it.
+#[tauri::command]
+pub fn git_stash_apply(
+    state: State<AppState>,
+    index: Option<usize>,
+) -> Result<MergeResult, AppError> {
+    let mut service = get_git_service(&state)?;
+    service.stash_apply(index.unwrap_or(0))
+}
+
+/// Apply a stash entry (default: the latest) and drop it if it applied cleanly.
+#[tauri::command]
+pub fn git_stash_pop(
+    state: State<AppState>,
+    index: Option<usize>,
+) -> Result<MergeResult, AppError> {
+    let mut service = get_git_service(&state)?;
+    service.stash_pop(index.unwrap_or(0))
+}
+
+/// Delete a stash entry.
+#[tauri::command]
+pub fn git_stash_drop(state: State<AppState>, index: usize) -> Result<(), AppError> {
+    let mut service = get_git_service(&state)?;
+    service.stash_drop(index)
+}
@@ -1,10 +1,11 @@
 use std::path::Path;
+use std::sync::atomic::Ordering;
 
-use tauri::State;
+use tauri::{Emitter, State};
 
 use crate::commands::file_commands::AppState;
 use crate::models::error::AppError;
-use crate::models::git::RepoInfo;
+use crate::models::git::{ReleaseManifest, RemoteAuth, RepoInfo};
 use crate::services::git_service::GitService;
 
 /// Open an existing git repository and store its path in the app state.
@@ -63,3 +64,66 @@ pub fn repo_info(state: State<AppState>) -> Result<RepoInfo, AppError> {
     let service = GitService::open(Path::new(path_str))?;
     service.repo_info()
 }
+
+/// Export the tables at `tag` as a `.zip` or `.tar.gz` release archive at
+/// `dest`, with a manifest and a changelog since the previous tag.
+#[tauri::command]
+pub fn repo_export_release(
+    state: State<AppState>,
+    tag: String,
+    dest: String,
+) -> Result<ReleaseManifest, AppError> {
+    let guard = state.repo_path.lock().unwrap();
+    let path_str = guard.as_ref().ok_or(AppError::NoRepo)?;
+    let service = GitService::open(Path::new(path_str))?;
+    service.export_release(&tag, Path::new(&dest))
+}
+
+/// Event carrying `TransferProgress` updates for clone, fetch, pull and push.
+pub const TRANSFER_PROGRESS_EVENT: &str = "git-transfer-progress";
+
+/// Clone a repository from a URL or local path into `dest`, emitting
+/// progress events, then open it. Cancel with `repo_cancel_transfer`.
+#[tauri::command]
+pub async fn repo_clone(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    url: String,
+    dest: String,
+    branch: Option<String>,
+    depth: Option<u32>,
+    auth: Option<RemoteAuth>,
+) -> Result<RepoInfo, AppError> {
+    let cancel = state.cancel_transfer.clone();
+    cancel.store(false, Ordering::Relaxed);
+
+    let path = dest.clone();
+    let info = tauri::async_runtime::spawn_blocking(move || {
+        let mut emit = |progress| {
+            let _ = app.emit(TRANSFER_PROGRESS_EVENT, progress);
+        };
+        let service = GitService::clone(
+            &url,
+            Path::new(&path),
+            branch.as_deref(),
+            depth,
+            &auth.unwrap_or_default(),
+            &cancel,
+            &mut emit,
+        )?;
+        service.repo_info()
+    })
+    .await
+    .map_err(|e| AppError::IoError(std::io::Error::other(e.to_string())))??;
+
+    let mut guard = state.repo_path.lock().unwrap();
+    *guard = Some(dest);
+
+    Ok(info)
+}
+
+/// Abort the running clone, fetch, pull or push.
+#[tauri::command]
+pub fn repo_cancel_transfer(state: State<AppState>) {
+    state.cancel_transfer.store(true, Ordering::Relaxed);
+}
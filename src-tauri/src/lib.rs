@@ -4,7 +4,8 @@ mod services;
 mod utils;
 
 use commands::file_commands::AppState;
-use std::sync::Mutex;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -14,6 +15,7 @@ pub fn run() {
         .plugin(tauri_plugin_shell::init())
         .manage(AppState {
             repo_path: Mutex::new(None),
+            cancel_transfer: Arc::new(AtomicBool::new(false)),
         })
         .invoke_handler(tauri::generate_handler![
             // Repo
@@ -21,6 +23,9 @@ pub fn run() {
             commands::repo_commands::repo_init,
             commands::repo_commands::repo_open_dialog,
             commands::repo_commands::repo_info,
+            commands::repo_commands::repo_export_release,
+            commands::repo_commands::repo_clone,
+            commands::repo_commands::repo_cancel_transfer,
             // Files
             commands::file_commands::file_read_csv,
             commands::file_commands::file_write_csv,
@@ -30,17 +35,54 @@ pub fn run() {
             // Git
             commands::git_commands::git_status,
             commands::git_commands::git_commit,
+            commands::git_commands::git_reset,
+            commands::git_commands::git_reflog,
+            commands::git_commands::git_reflog_recover,
             commands::git_commands::git_log,
+            commands::git_commands::git_commit_details,
             commands::git_commands::git_show_file,
+            commands::git_commands::git_restore_file,
             commands::git_commands::git_branches,
             commands::git_commands::git_create_branch,
+            commands::git_commands::git_delete_branch,
+            commands::git_commands::git_rename_branch,
+            commands::git_commands::git_track_branch,
+            commands::git_commands::git_set_upstream,
             commands::git_commands::git_checkout,
+            commands::git_commands::git_tags,
+            commands::git_commands::git_create_tag,
+            commands::git_commands::git_delete_tag,
             commands::git_commands::git_merge,
+            commands::git_commands::git_merge_preview,
             commands::git_commands::git_push,
+            commands::git_commands::git_push_tag,
+            commands::git_commands::git_export_bundle,
+            commands::git_commands::git_fetch_bundle,
             commands::git_commands::git_pull,
+            commands::git_commands::git_fetch,
+            commands::git_commands::git_rebase_continue,
+            commands::git_commands::git_rebase_abort,
+            commands::git_commands::git_revert,
+            commands::git_commands::git_cherry_pick,
+            commands::git_commands::git_pick_cells,
             commands::git_commands::git_remotes,
             commands::git_commands::git_add_remote,
+            commands::git_commands::git_remove_remote,
+            commands::git_commands::git_rename_remote,
+            commands::git_commands::git_set_remote_url,
+            commands::git_commands::git_set_remote_push_urls,
             commands::git_commands::git_resolve_conflicts,
+            commands::git_commands::git_conflicts,
+            commands::git_commands::git_merge_abort,
+            commands::git_commands::git_repo_state,
+            commands::git_commands::git_merge_strategies,
+            commands::git_commands::git_set_merge_strategy,
+            commands::git_commands::git_remove_merge_strategy,
+            commands::git_commands::git_stash_save,
+            commands::git_commands::git_stash_list,
+            commands::git_commands::git_stash_apply,
+            commands::git_commands::git_stash_pop,
+            commands::git_commands::git_stash_drop,
         ])
         .run(tauri::generate_context!())
         .expect("error while running Ledgit");
@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+
+/// A single cell whose value differs between two versions of a table.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CellChange {
+    pub row_key: String,
+    pub column: String,
+    pub old_value: String,
+    pub new_value: String,
+}
+
+/// One cell of a table, addressed by row key and column name.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CellRef {
+    pub row_key: String,
+    pub column: String,
+}
+
+/// Cell-level differences between two versions of a table.
+/// Rows are identified by their key (see `table_diff::Table::row_keys`).
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct TableDiff {
+    pub added_rows: Vec<String>,
+    pub removed_rows: Vec<String>,
+    pub modified_cells: Vec<CellChange>,
+    pub added_columns: Vec<String>,
+    pub removed_columns: Vec<String>,
+    pub summary: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FileChangeKind {
+    Added,
+    Modified,
+    Deleted,
+    Renamed,
+}
+
+/// Change to one file, with a table diff when both sides parse as CSV/TSV.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FileDiff {
+    pub path: String,
+    pub old_path: Option<String>,
+    pub change: FileChangeKind,
+    pub table: Option<TableDiff>,
+}
+
+/// A cell that both sides of a three-way merge changed differently.
+/// `None` means the cell's row or column does not exist on that side.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CellConflict {
+    pub row_key: String,
+    pub column: String,
+    pub ancestor: Option<String>,
+    pub ours: Option<String>,
+    pub theirs: Option<String>,
+}
@@ -25,6 +25,61 @@ pub enum AppError {
 
     #[error("Repository already exists at {0}")]
     RepoExists(String),
+
+    #[error("Row not found: {0}")]
+    RowNotFound(String),
+
+    #[error("Column not found: {0}")]
+    ColumnNotFound(String),
+
+    #[error("No merge in progress")]
+    NoMergeInProgress,
+
+    #[error("No rebase in progress")]
+    NoRebaseInProgress,
+
+    #[error("Branch {0} has commits not merged into HEAD or its upstream")]
+    UnmergedBranch(String),
+
+    #[error("Commit {commit} is already pushed to {upstream}; amending it would rewrite shared history")]
+    AlreadyPushed { commit: String, upstream: String },
+
+    #[error("Hard reset discards uncommitted changes; confirm it against HEAD {0}")]
+    ConfirmationRequired(String),
+
+    #[error("Unsupported archive type for {0}: use .zip or .tar.gz")]
+    UnsupportedArchive(String),
+
+    #[error("Not a git bundle: {0}")]
+    InvalidBundle(String),
+
+    #[error("The bundle needs commits this repository does not have: {}", .0.join(", "))]
+    MissingPrerequisites(Vec<String>),
+
+    #[error("Operation cancelled")]
+    Cancelled,
+
+    #[error("Authentication failed for {0}")]
+    AuthFailed(String),
+
+    #[error("SSH key {0} needs a passphrase")]
+    PassphraseRequired(String),
+
+    #[error("Unknown host key for {host}: {fingerprint}")]
+    UnknownHostKey { host: String, fingerprint: String },
+
+    #[error("Could not reach {0}")]
+    HostUnreachable(String),
+
+    #[error("Push to {remote} rejected for {}: the remote has commits you don't have, pull first", .refs.join(", "))]
+    PushRejected { remote: String, refs: Vec<String> },
+
+    #[error("Remote branch {branch} is at {actual}, not the expected {expected}; fetch and review before forcing")]
+    StaleLease {
+        branch: String,
+        expected: String,
+        actual: String,
+    },
 }
 
 impl Serialize for AppError {
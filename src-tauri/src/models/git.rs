@@ -1,13 +1,50 @@
 use serde::{Deserialize, Serialize};
 
+use crate::models::diff::{CellConflict, FileChangeKind, FileDiff};
+use crate::models::sheet::Column;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Commit {
     pub hash: String,
     pub short_hash: String,
     pub message: String,
     pub author: String,
+    pub author_email: String,
+    pub author_timestamp: String,
+    pub committer: String,
+    pub committer_email: String,
+    /// Commit (committer) time.
     pub timestamp: String,
+    pub parents: Vec<String>,
+    pub refs: Vec<String>,
+    /// Position in the history graph, when listed by `log` without a file filter.
+    pub graph: Option<GraphRow>,
+}
+
+/// Where a commit sits in the drawn history graph. Lanes are columns,
+/// numbered from 0 on the left.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct GraphRow {
+    pub lane: usize,
+    /// Lanes of other lines passing this row untouched.
+    pub through: Vec<usize>,
+    /// Other lanes whose line ends at this commit (branches forking from it).
+    pub joining: Vec<usize>,
+    /// Lane each parent continues in below this row, in parent order.
+    pub parent_lanes: Vec<usize>,
+}
+
+/// Which history `log` walks, and in what order.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct LogOptions {
+    /// Every branch, remote-tracking branch and tag, like `git log --all`.
+    pub all: bool,
+    /// Branches, tags or commits to start from instead of HEAD.
     pub refs: Vec<String>,
+    /// Parents never before children, like `git log --topo-order`. Always
+    /// the case without a file filter, where graph lanes are computed.
+    pub topo: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -23,6 +60,22 @@ pub struct RepoStatus {
 pub struct BranchList {
     pub branches: Vec<String>,
     pub current: String,
+    /// Local branches with their upstream and ahead/behind counts.
+    pub local: Vec<BranchInfo>,
+    /// Remote-tracking branches, e.g. `origin/main`.
+    pub remote: Vec<BranchInfo>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BranchInfo {
+    pub name: String,
+    pub hash: String,
+    /// Upstream as a remote-tracking branch name, e.g. `origin/main`.
+    pub upstream: Option<String>,
+    /// Commits on this branch not on its upstream.
+    pub ahead: usize,
+    /// Commits on the upstream not on this branch.
+    pub behind: usize,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -31,6 +84,12 @@ pub struct RepoInfo {
     pub name: String,
     pub branch: String,
     pub remote_url: Option<String>,
+    /// Upstream of the current branch, e.g. `origin/main`.
+    pub upstream: Option<String>,
+    /// Commits on the current branch not yet on its upstream.
+    pub ahead: usize,
+    /// Fetched upstream commits not yet merged into the current branch.
+    pub behind: usize,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -43,6 +102,9 @@ pub struct MergeResult {
 pub struct Remote {
     pub name: String,
     pub url: String,
+    /// Push URLs when they differ from `url`; pushes go to every one.
+    pub push_urls: Vec<String>,
+    pub fetch_refspecs: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -50,4 +112,391 @@ pub struct PullResult {
     pub updated: bool,
     pub new_commits: u32,
     pub conflicts: Option<Vec<String>>,
+    /// Progress of a pull in rebase mode.
+    pub rebase: Option<RebaseResult>,
+}
+
+/// State of a rebase after running as far as it could.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RebaseResult {
+    /// True once every commit has been replayed and the branch updated.
+    pub finished: bool,
+    /// Commits replayed so far, out of `total`.
+    pub applied: usize,
+    pub total: usize,
+    /// Commit being replayed when the rebase stopped on conflicts.
+    pub stopped_at: Option<Commit>,
+    pub conflicts: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictKind {
+    BothModified,
+    BothAdded,
+    DeletedByUs,
+    DeletedByThem,
+    Renamed,
+}
+
+/// One side (index stage) of a conflicted file.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConflictSide {
+    pub path: String,
+    pub oid: String,
+    /// Blob contents, or `None` if the blob is not valid UTF-8.
+    pub content: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConflictFile {
+    pub path: String,
+    pub kind: ConflictKind,
+    pub ancestor: Option<ConflictSide>,
+    pub ours: Option<ConflictSide>,
+    pub theirs: Option<ConflictSide>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConflictList {
+    /// Name of the branch being merged in, if known.
+    pub source: Option<String>,
+    pub merge_head: Option<String>,
+    pub files: Vec<ConflictFile>,
+}
+
+/// Multi-step operation the repository is in the middle of.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RepoOperation {
+    None,
+    Merge,
+    Revert,
+    CherryPick,
+    Rebase,
+    Bisect,
+    ApplyMailbox,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RepoState {
+    pub operation: RepoOperation,
+    pub head: Option<String>,
+    /// HEAD before the operation started (ORIG_HEAD).
+    pub orig_head: Option<String>,
+    /// Commits being applied: MERGE_HEAD(s), REVERT_HEAD or CHERRY_PICK_HEAD.
+    pub incoming: Vec<String>,
+    /// Commit a rebase is replaying onto.
+    pub onto: Option<String>,
+    pub source: Option<String>,
+    pub conflicts: Vec<String>,
+}
+
+/// A file the merge cannot resolve by itself, with the cells both sides
+/// changed differently (empty if the file is not a table).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MergePreviewConflict {
+    pub path: String,
+    pub kind: ConflictKind,
+    pub cells: Vec<CellConflict>,
+}
+
+/// What merging `source` into HEAD would do, computed without touching
+/// the index or the working tree.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MergePreview {
+    pub source: String,
+    pub up_to_date: bool,
+    pub fast_forward: bool,
+    /// Merge result compared with HEAD.
+    pub files: Vec<FileDiff>,
+    pub conflicts: Vec<MergePreviewConflict>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeStrategy {
+    /// Cell-level three-way merge (the default for tables).
+    #[default]
+    Cells,
+    Ours,
+    Theirs,
+    /// Keep every row from both sides, dropping theirs rows whose key ours already has.
+    /// Rows either side deleted since the merge base stay deleted.
+    Union,
+    /// Cell-level merge, except owned columns always take the owning branch's values.
+    ColumnOwnership,
+}
+
+/// Columns whose values are decided by one branch.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ColumnOwner {
+    pub branch: String,
+    pub columns: Vec<String>,
+}
+
+/// Merge strategy for files matching a glob pattern, stored in the repo's git config.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct MergeStrategyRule {
+    pub pattern: String,
+    #[serde(default)]
+    pub strategy: MergeStrategy,
+    /// Columns identifying a row; defaults to the first column.
+    #[serde(default)]
+    pub key_columns: Vec<String>,
+    #[serde(default)]
+    pub column_owners: Vec<ColumnOwner>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CheckoutResult {
+    pub switched: bool,
+    /// Uncommitted files the checkout would have overwritten.
+    pub blocked: Vec<String>,
+    /// Whether the blocking changes were auto-stashed and re-applied.
+    pub stashed: bool,
+    /// Files left conflicted when re-applying the auto-stash.
+    pub conflicts: Vec<String>,
+    /// The auto-stash is still in the stash list, because it conflicted or
+    /// could not be applied on the new branch.
+    pub stash_kept: bool,
+    /// Why the auto-stash could not be applied, if it failed outright.
+    pub stash_error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StashEntry {
+    pub index: usize,
+    pub message: String,
+    pub hash: String,
+    /// Commit the stash was made on top of.
+    pub base: String,
+    pub timestamp: String,
+    pub files: Vec<FileDiff>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferStage {
+    /// Downloading objects.
+    #[default]
+    Receiving,
+    /// Resolving deltas in the received pack.
+    Resolving,
+    /// Writing files to the working directory.
+    CheckingOut,
+    /// Uploading objects during a push.
+    Sending,
+}
+
+/// Progress of a network operation, emitted as the `git-transfer-progress` event.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct TransferProgress {
+    pub stage: TransferStage,
+    pub received_objects: usize,
+    pub indexed_objects: usize,
+    pub total_objects: usize,
+    pub indexed_deltas: usize,
+    pub total_deltas: usize,
+    pub received_bytes: usize,
+    /// Files written so far during `CheckingOut`.
+    pub checked_out: usize,
+    pub checkout_total: usize,
+    /// Objects and bytes uploaded so far during `Sending`, out of `total_objects`.
+    pub sent_objects: usize,
+    pub sent_bytes: usize,
+}
+
+/// Credentials for a remote operation. Anything left empty falls back to the
+/// ssh-agent, default SSH keys, or the git credential helper.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct RemoteAuth {
+    pub username: Option<String>,
+    /// HTTPS password or access token.
+    pub password: Option<String>,
+    /// Private key file for SSH.
+    pub ssh_key_path: Option<String>,
+    pub passphrase: Option<String>,
+    /// Host key fingerprints (`SHA256:...`) the user accepted for hosts not in known_hosts.
+    pub trusted_host_keys: Vec<String>,
+}
+
+/// A ref moved by a fetch. `old` is `None` for new refs, `new` for pruned ones.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RefUpdate {
+    pub name: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FetchResult {
+    pub remote: String,
+    pub updated: Vec<RefUpdate>,
+    pub received_objects: usize,
+    pub received_bytes: usize,
+}
+
+/// What an exported bundle carries. A receiver needs the `prerequisites`
+/// commits already to fetch from it.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BundleInfo {
+    pub refs: Vec<String>,
+    pub prerequisites: Vec<String>,
+    pub objects: usize,
+}
+
+/// Outcome for one ref of a push. `message` is the remote's reason when rejected.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PushRefStatus {
+    pub name: String,
+    pub ok: bool,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PushResult {
+    pub remote: String,
+    pub refs: Vec<PushRefStatus>,
+    /// Push URLs that failed when the remote has several. The others were
+    /// still pushed, so a non-empty list means the push partly succeeded.
+    pub failed_urls: Vec<PushUrlError>,
+}
+
+/// A push URL that could not be updated, with the reason.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PushUrlError {
+    pub url: String,
+    pub error: String,
+}
+
+/// Outcome of applying a commit's changes again (cherry-pick) or in reverse
+/// (revert). Either `commit` is the new commit, or `conflicts` lists files
+/// left for the user to resolve before committing with `resolve_conflicts`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReplayResult {
+    pub commit: Option<Commit>,
+    pub conflicts: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RestoreResult {
+    pub restored: bool,
+    /// The file had uncommitted changes. Without `force` nothing is written.
+    pub uncommitted: bool,
+    pub staged: bool,
+    /// What restoring changes relative to the working copy.
+    pub changes: FileDiff,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ResetMode {
+    /// Move the branch only; the index and working tree keep the changes.
+    Soft,
+    /// Move the branch and the index; changes stay in the working tree.
+    Mixed,
+    /// Move the branch and discard all uncommitted changes.
+    Hard,
+}
+
+/// One entry of a ref's reflog, newest first (`index` 0).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReflogEntry {
+    pub index: usize,
+    pub old_hash: String,
+    pub new_hash: String,
+    pub message: String,
+    pub committer: String,
+    pub timestamp: String,
+    /// Table-diff summary of what the entry changed, per file.
+    pub summary: String,
+}
+
+/// Outcome of a reset. Resetting to `previous` undoes it.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResetResult {
+    pub previous: String,
+    pub commit: Commit,
+    /// The reflog entry the reset recorded for HEAD.
+    pub reflog: Option<ReflogEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TagInfo {
+    pub name: String,
+    pub annotated: bool,
+    /// Message and tagger of an annotated tag.
+    pub message: Option<String>,
+    pub tagger: Option<String>,
+    /// The tagged commit.
+    pub commit: Commit,
+}
+
+/// One table in a release archive.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ManifestFile {
+    pub path: String,
+    pub rows: usize,
+    pub columns: Vec<Column>,
+    pub sha256: String,
+}
+
+/// Contents of a release archive, also written into it as `manifest.json`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReleaseManifest {
+    pub tag: String,
+    pub commit: Commit,
+    /// The tag the changelog is measured from, if there is an earlier one.
+    pub previous_tag: Option<String>,
+    pub files: Vec<ManifestFile>,
+    pub changelog: Vec<FileDiff>,
+}
+
+/// A commit author or committer, with the time in their own timezone.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Identity {
+    pub name: String,
+    pub email: String,
+    /// RFC 3339 with the original UTC offset, e.g. `2024-03-01T09:30:00+01:00`.
+    pub timestamp: String,
+    pub offset_minutes: i32,
+}
+
+/// A `Key: value` line from the end of a commit message, e.g. `Signed-off-by`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Trailer {
+    pub key: String,
+    pub value: String,
+}
+
+/// What a commit changed in one file, counted in table terms. Counts are
+/// zero for files that are not CSV/TSV tables.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FileStats {
+    pub path: String,
+    pub old_path: Option<String>,
+    pub change: FileChangeKind,
+    pub rows_added: usize,
+    pub rows_removed: usize,
+    pub rows_modified: usize,
+    pub cells_changed: usize,
+    pub columns_added: usize,
+    pub columns_removed: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CommitDetails {
+    pub hash: String,
+    pub short_hash: String,
+    pub subject: String,
+    pub body: String,
+    pub trailers: Vec<Trailer>,
+    pub author: Identity,
+    pub committer: Identity,
+    pub parents: Vec<String>,
+    pub refs: Vec<String>,
+    /// Changes against the first parent, or everything for a root commit.
+    pub files: Vec<FileStats>,
 }
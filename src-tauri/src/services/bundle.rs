@@ -0,0 +1,164 @@
+use std::path::Path;
+
+use crate::models::error::AppError;
+
+const SIGNATURE_V2: &str = "# v2 git bundle";
+const SIGNATURE_V3: &str = "# v3 git bundle";
+
+/// A git bundle file: the refs it carries, the commits the receiver must
+/// already have, and the packfile with everything else.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bundle {
+    pub prerequisites: Vec<git2::Oid>,
+    pub refs: Vec<(String, git2::Oid)>,
+    pub pack: Vec<u8>,
+}
+
+impl Bundle {
+    /// Read a v2 or v3 (SHA-1) bundle, as written by `git bundle create`.
+    pub fn read(path: &Path) -> Result<Self, AppError> {
+        let bytes = std::fs::read(path)?;
+        let invalid = || invalid_bundle(path);
+
+        let mut lines = Vec::new();
+        let mut offset = 0;
+        loop {
+            let end = bytes[offset..]
+                .iter()
+                .position(|&b| b == b'\n')
+                .ok_or_else(invalid)?;
+            let line = std::str::from_utf8(&bytes[offset..offset + end]).map_err(|_| invalid())?;
+            offset += end + 1;
+            if line.is_empty() {
+                break;
+            }
+            lines.push(line);
+        }
+
+        let mut lines = lines.into_iter();
+        match lines.next() {
+            Some(SIGNATURE_V2) | Some(SIGNATURE_V3) => {}
+            _ => return Err(invalid()),
+        }
+
+        let mut bundle = Bundle {
+            prerequisites: Vec::new(),
+            refs: Vec::new(),
+            pack: bytes[offset..].to_vec(),
+        };
+        for line in lines {
+            if let Some(capability) = line.strip_prefix('@') {
+                // A filtered bundle's pack is missing objects, so it can't be unbundled
+                if capability != "object-format=sha1" {
+                    return Err(invalid());
+                }
+            } else if let Some(rest) = line.strip_prefix('-') {
+                let oid = rest.split(' ').next().unwrap_or("");
+                bundle
+                    .prerequisites
+                    .push(git2::Oid::from_str(oid).map_err(|_| invalid())?);
+            } else {
+                let (oid, name) = line.split_once(' ').ok_or_else(invalid)?;
+                let oid = git2::Oid::from_str(oid).map_err(|_| invalid())?;
+                bundle.refs.push((name.to_string(), oid));
+            }
+        }
+
+        if !bundle.pack.starts_with(b"PACK") {
+            return Err(invalid());
+        }
+        Ok(bundle)
+    }
+
+    /// Write the bundle in v2 format, readable by `git bundle` and `git fetch`.
+    /// `comments` gives each prerequisite's subject line.
+    pub fn write(&self, path: &Path, comments: &[String]) -> Result<(), AppError> {
+        let mut header = format!("{}\n", SIGNATURE_V2);
+        for (i, oid) in self.prerequisites.iter().enumerate() {
+            match comments.get(i).filter(|c| !c.is_empty()) {
+                Some(comment) => header.push_str(&format!("-{} {}\n", oid, comment)),
+                None => header.push_str(&format!("-{}\n", oid)),
+            }
+        }
+        for (name, oid) in &self.refs {
+            header.push_str(&format!("{} {}\n", oid, name));
+        }
+        header.push('\n');
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut bytes = header.into_bytes();
+        bytes.extend_from_slice(&self.pack);
+        std::fs::write(path, bytes)?;
+        Ok(())
+    }
+
+    /// Number of objects in the packfile, from its header.
+    pub fn object_count(&self) -> usize {
+        self.pack
+            .get(8..12)
+            .map(|n| u32::from_be_bytes([n[0], n[1], n[2], n[3]]) as usize)
+            .unwrap_or(0)
+    }
+}
+
+fn invalid_bundle(path: &Path) -> AppError {
+    AppError::InvalidBundle(path.display().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_bundle_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("field.bundle");
+        let base = git2::Oid::from_str("1111111111111111111111111111111111111111").unwrap();
+        let tip = git2::Oid::from_str("2222222222222222222222222222222222222222").unwrap();
+        let mut pack = b"PACK\0\0\0\x02\0\0\0\x03".to_vec();
+        pack.extend_from_slice(&[0; 20]);
+        let bundle = Bundle {
+            prerequisites: vec![base],
+            refs: vec![("refs/heads/main".to_string(), tip)],
+            pack,
+        };
+
+        bundle.write(&path, &["Base commit".to_string()]).unwrap();
+        let content = std::fs::read(&path).unwrap();
+        assert!(content.starts_with(
+            format!(
+                "# v2 git bundle\n-{} Base commit\n{} refs/heads/main\n\nPACK",
+                base, tip
+            )
+            .as_bytes()
+        ));
+
+        let read = Bundle::read(&path).unwrap();
+        assert_eq!(read, bundle);
+        assert_eq!(read.object_count(), 3);
+    }
+
+    #[test]
+    fn test_rejects_other_files() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("data.csv");
+        std::fs::write(&path, "id,v\n1,a\n\n").unwrap();
+        assert!(matches!(
+            Bundle::read(&path),
+            Err(AppError::InvalidBundle(_))
+        ));
+
+        // v3 with an unsupported object format, a filter or an unknown capability
+        for capability in ["object-format=sha256", "filter=blob:none", "unknown"] {
+            let header = format!("# v3 git bundle\n@{}\n\nPACK", capability);
+            std::fs::write(&path, header).unwrap();
+            assert!(matches!(
+                Bundle::read(&path),
+                Err(AppError::InvalidBundle(_))
+            ));
+        }
+    }
+}
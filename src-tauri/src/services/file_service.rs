@@ -305,6 +305,31 @@ fn infer_value(raw: &str) -> serde_json::Value {
     serde_json::Value::String(raw.to_string())
 }
 
+/// Columns with inferred types for raw records, as `read_csv` reports them.
+pub fn infer_columns(headers: &[String], records: &[Vec<String>]) -> Vec<Column> {
+    let rows: Vec<Row> = records
+        .iter()
+        .take(100)
+        .map(|record| {
+            headers
+                .iter()
+                .zip(record)
+                .map(|(header, field)| (header.clone(), infer_value(field)))
+                .collect()
+        })
+        .collect();
+
+    headers
+        .iter()
+        .zip(infer_column_types(headers, &rows))
+        .map(|(h, col_type)| Column {
+            field: h.clone(),
+            header_name: h.clone(),
+            col_type,
+        })
+        .collect()
+}
+
 /// Sample up to the first 100 rows to determine the predominant type for each column.
 fn infer_column_types(headers: &[String], rows: &[Row]) -> Vec<ColumnType> {
     let sample_size = rows.len().min(100);
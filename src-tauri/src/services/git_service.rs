@@ -1,13 +1,32 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::path::Path;
+use std::sync::atomic::AtomicBool;
 
 use git2::{
-    BranchType, DiffOptions, MergeOptions, Repository, Signature, Sort,
-    StatusOptions, build::CheckoutBuilder,
+    BranchType, CheckoutNotificationType, ConfigLevel, Delta, DiffOptions, Direction, ErrorCode,
+    FetchOptions, MergeOptions, ObjectType, PushOptions, Repository, RepositoryState, ResetType,
+    Signature, Sort, StashApplyOptions, StashFlags, StashSaveOptions, StatusOptions, TreeWalkMode,
+    TreeWalkResult, build::{CheckoutBuilder, RepoBuilder},
 };
 
+use crate::models::diff::{CellRef, FileChangeKind, FileDiff};
 use crate::models::error::AppError;
-use crate::models::git::{BranchList, Commit, MergeResult, PullResult, Remote, RepoInfo, RepoStatus};
+use crate::models::git::{
+    BranchInfo, BranchList, BundleInfo, CheckoutResult, ColumnOwner, Commit, CommitDetails,
+    ConflictFile, ConflictKind, ConflictList, ConflictSide, FetchResult, FileStats, Identity,
+    LogOptions, ManifestFile, MergePreview, MergePreviewConflict, MergeResult, MergeStrategy,
+    MergeStrategyRule, PullResult, PushRefStatus, PushResult, PushUrlError, RebaseResult, RefUpdate,
+    ReflogEntry, ReleaseManifest, Remote, RemoteAuth, ReplayResult, RepoInfo, RepoOperation,
+    RepoState, RepoStatus, ResetMode, ResetResult, RestoreResult, StashEntry, TagInfo, Trailer,
+    TransferProgress,
+};
+use crate::services::bundle::Bundle;
+use crate::services::file_service;
+use crate::services::graph;
+use crate::services::merge_strategy::{self, Merged};
+use crate::services::release::{self, ArchiveFormat};
+use crate::services::table_diff::{self, Table};
+use crate::services::transfer::{self, Transfer};
 
 pub struct GitService {
     repo: Repository,
@@ -20,6 +39,44 @@ impl GitService {
         Ok(Self { repo })
     }
 
+    /// Clone `url` (a URL or local path) into `dest`, checking out `branch`
+    /// or the remote's default branch. `depth` limits history on network
+    /// remotes; local remotes always clone in full. Transfer and checkout
+    /// progress go to `progress`, and setting `cancel` aborts the clone.
+    pub fn clone(
+        url: &str,
+        dest: &Path,
+        branch: Option<&str>,
+        depth: Option<u32>,
+        auth: &RemoteAuth,
+        cancel: &AtomicBool,
+        progress: &mut dyn FnMut(TransferProgress),
+    ) -> Result<Self, AppError> {
+        let transfer = Transfer::new(url, auth, cancel, progress);
+
+        let mut fetch_opts = FetchOptions::new();
+        fetch_opts.remote_callbacks(transfer.callbacks());
+        if let Some(depth) = depth.filter(|_| !transfer::is_local(url)) {
+            fetch_opts.depth(depth as i32);
+        }
+
+        let mut checkout = CheckoutBuilder::new();
+        checkout.progress(|_, checked_out, total| {
+            transfer.report(transfer::checkout_progress(checked_out, total));
+        });
+
+        let mut builder = RepoBuilder::new();
+        builder.fetch_options(fetch_opts).with_checkout(checkout);
+        if let Some(branch) = branch {
+            builder.branch(branch);
+        }
+
+        let repo = builder
+            .clone(url, dest)
+            .map_err(|err| transfer.map_error(err))?;
+        Ok(Self { repo })
+    }
+
     /// Initialize a new git repository at the given path.
     /// Creates a .gitattributes file and makes an initial commit.
     pub fn init(path: &Path) -> Result<Self, AppError> {
@@ -146,25 +203,163 @@ impl GitService {
         Ok(Self::commit_to_model(&commit))
     }
 
+    /// Replace HEAD with a commit that also includes `files` and, unless
+    /// `message` is empty, carries the new message. The original author is
+    /// kept. Refuses when HEAD is already on the branch's upstream.
+    pub fn amend(&self, message: &str, files: &[String]) -> Result<Commit, AppError> {
+        let head = self.repo.head()?;
+        let commit = head.peel_to_commit()?;
+
+        if head.is_branch() {
+            let name = head.shorthand().unwrap_or("").to_string();
+            let branch = self.repo.find_branch(&name, BranchType::Local)?;
+            if let Ok(upstream) = branch.upstream() {
+                let pushed = upstream.get().target().is_some_and(|tip| {
+                    tip == commit.id()
+                        || self.repo.graph_descendant_of(tip, commit.id()).unwrap_or(false)
+                });
+                if pushed {
+                    return Err(AppError::AlreadyPushed {
+                        commit: commit.id().to_string()[..7].to_string(),
+                        upstream: upstream.name()?.unwrap_or("").to_string(),
+                    });
+                }
+            }
+        }
+
+        let mut index = self.stage_resolved(files)?;
+        let tree = self.repo.find_tree(index.write_tree()?)?;
+        let sig = Self::default_signature(&self.repo)?;
+        let message = if message.trim().is_empty() {
+            None
+        } else {
+            Some(message)
+        };
+
+        let oid = commit.amend(Some("HEAD"), None, Some(&sig), None, message, Some(&tree))?;
+        let commit = self.repo.find_commit(oid)?;
+        Ok(Self::commit_to_model(&commit))
+    }
+
+    /// Move the current branch to `target`. A hard reset discards uncommitted
+    /// changes, so it only runs when `confirm` is the full hash of the
+    /// current HEAD, as shown to the user.
+    pub fn reset(
+        &self,
+        target: &str,
+        mode: ResetMode,
+        confirm: Option<&str>,
+    ) -> Result<ResetResult, AppError> {
+        let previous = self.repo.head()?.peel_to_commit()?.id().to_string();
+        if mode == ResetMode::Hard && confirm != Some(previous.as_str()) {
+            return Err(AppError::ConfirmationRequired(previous));
+        }
+
+        let commit = self.repo.revparse_single(target)?.peel_to_commit()?;
+        let kind = match mode {
+            ResetMode::Soft => ResetType::Soft,
+            ResetMode::Mixed => ResetType::Mixed,
+            ResetMode::Hard => ResetType::Hard,
+        };
+        let mut checkout = CheckoutBuilder::new();
+        checkout.force();
+        self.repo.reset(commit.as_object(), kind, Some(&mut checkout))?;
+
+        // HEAD has already moved, so the reflog entry is only a bonus
+        let reflog = self.repo.reflog("HEAD").ok();
+        Ok(ResetResult {
+            previous,
+            commit: Self::commit_to_model(&commit),
+            reflog: reflog
+                .as_ref()
+                .and_then(|reflog| reflog.get(0))
+                .map(|entry| self.reflog_entry_to_model(0, &entry)),
+        })
+    }
+
+    /// Entries of a ref's reflog, newest first, each summarising the table
+    /// changes it made. `reference` is `HEAD`, a full ref or a branch name.
+    pub fn reflog(&self, reference: &str) -> Result<Vec<ReflogEntry>, AppError> {
+        let name = self.reflog_name(reference);
+        let reflog = self.repo.reflog(&name)?;
+        Ok(reflog
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| self.reflog_entry_to_model(index, &entry))
+            .collect())
+    }
+
+    /// Create branch `branch` at the commit reflog entry `index` of
+    /// `reference` moved to, bringing back commits nothing else points at.
+    pub fn recover_reflog_entry(
+        &self,
+        reference: &str,
+        index: usize,
+        branch: &str,
+    ) -> Result<Commit, AppError> {
+        let name = self.reflog_name(reference);
+        let reflog = self.repo.reflog(&name)?;
+        let entry = reflog.get(index).ok_or_else(|| {
+            AppError::GitError(git2::Error::from_str(&format!(
+                "No entry {} in the reflog of {}",
+                index, reference
+            )))
+        })?;
+        let commit = self.repo.find_commit(entry.id_new())?;
+        self.repo.branch(branch, &commit, false)?;
+        Ok(Self::commit_to_model(&commit))
+    }
+
     /// Get commit log, optionally filtered by file path, with pagination.
+    /// `options` picks the starting refs and ordering; without a file filter
+    /// each commit carries its graph lanes, computed over the whole walk so
+    /// pages line up.
     pub fn log(
         &self,
         file: Option<&str>,
         limit: usize,
         offset: usize,
+        options: &LogOptions,
     ) -> Result<Vec<Commit>, AppError> {
         let ref_map = self.build_ref_map();
 
         let mut revwalk = self.repo.revwalk()?;
-        revwalk.push_head()?;
-        revwalk.set_sorting(Sort::TIME)?;
+        if options.all {
+            for reference in self.repo.references()? {
+                let reference = reference?;
+                if let Ok(commit) = reference.peel_to_commit() {
+                    revwalk.push(commit.id())?;
+                }
+            }
+            if let Ok(head) = self.repo.head() {
+                revwalk.push(head.peel_to_commit()?.id())?;
+            }
+        } else if !options.refs.is_empty() {
+            for name in &options.refs {
+                revwalk.push(self.repo.revparse_single(name)?.peel_to_commit()?.id())?;
+            }
+        } else {
+            revwalk.push_head()?;
+        }
+        // Lanes need every child before its parents, which a time sort alone
+        // doesn't give when clocks were skewed
+        if options.topo || file.is_none() {
+            revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+        } else {
+            revwalk.set_sorting(Sort::TIME)?;
+        }
 
         let mut commits = Vec::new();
         let mut skipped = 0;
+        let mut lanes = graph::Lanes::default();
 
         for oid_result in revwalk {
             let oid = oid_result?;
             let commit = self.repo.find_commit(oid)?;
+            let graph = match file {
+                Some(_) => None,
+                None => Some(lanes.next(oid, &commit.parent_ids().collect::<Vec<_>>())),
+            };
 
             // If filtering by file, check if the commit touches that file
             if let Some(file_path) = file {
@@ -180,7 +375,9 @@ impl GitService {
             }
 
             let refs = ref_map.get(&oid).cloned().unwrap_or_default();
-            commits.push(Self::commit_to_model_with_refs(&commit, refs));
+            let mut model = Self::commit_to_model_with_refs(&commit, refs);
+            model.graph = graph;
+            commits.push(model);
 
             if commits.len() >= limit {
                 break;
@@ -190,10 +387,72 @@ impl GitService {
         Ok(commits)
     }
 
+    /// Everything about one commit: subject and body, trailers, author and
+    /// committer in their own timezones, and per-file table change counts.
+    pub fn commit_details(&self, hash: &str) -> Result<CommitDetails, AppError> {
+        let commit = self.repo.revparse_single(hash)?.peel_to_commit()?;
+        let message = commit.message().unwrap_or("");
+        let (subject, body) = match message.split_once("\n\n") {
+            Some((subject, body)) => (subject, body.trim_end()),
+            None => (message.trim_end(), ""),
+        };
+        let trailers = git2::message_trailers_strs(message)?
+            .iter()
+            .map(|(key, value)| Trailer {
+                key: key.to_string(),
+                value: value.to_string(),
+            })
+            .collect();
+
+        let parent_tree = match commit.parent(0) {
+            Ok(parent) => Some(parent.tree()?),
+            Err(_) => None,
+        };
+        let files = self
+            .diff_trees(parent_tree.as_ref(), Some(&commit.tree()?))?
+            .into_iter()
+            .map(|file| {
+                let table = file.table.unwrap_or_default();
+                let rows_modified: HashSet<&str> = table
+                    .modified_cells
+                    .iter()
+                    .map(|c| c.row_key.as_str())
+                    .collect();
+                FileStats {
+                    path: file.path,
+                    old_path: file.old_path,
+                    change: file.change,
+                    rows_added: table.added_rows.len(),
+                    rows_removed: table.removed_rows.len(),
+                    rows_modified: rows_modified.len(),
+                    cells_changed: table.modified_cells.len(),
+                    columns_added: table.added_columns.len(),
+                    columns_removed: table.removed_columns.len(),
+                }
+            })
+            .collect();
+
+        let hash = commit.id().to_string();
+        let author = Self::identity(&commit.author());
+        let committer = Self::identity(&commit.committer());
+        Ok(CommitDetails {
+            short_hash: hash[..7].to_string(),
+            subject: subject.replace('\n', " "),
+            body: body.to_string(),
+            trailers,
+            author,
+            committer,
+            parents: commit.parent_ids().map(|oid| oid.to_string()).collect(),
+            refs: self.build_ref_map().remove(&commit.id()).unwrap_or_default(),
+            files,
+            hash,
+        })
+    }
+
     /// Show the contents of a file at a specific commit hash.
     pub fn show_file(&self, hash: &str, file_path: &str) -> Result<String, AppError> {
-        let oid = self.repo.revparse_single(hash)?.id();
-        let commit = self.repo.find_commit(oid)?;
+        // Peel so tags work as well as commits and branches
+        let commit = self.repo.revparse_single(hash)?.peel_to_commit()?;
         let tree = commit.tree()?;
 
         let entry = tree.get_path(Path::new(file_path)).map_err(|_| {
@@ -211,26 +470,223 @@ impl GitService {
         Ok(content.to_string())
     }
 
+    /// Write a file's content at commit `hash` into the working tree, and
+    /// with `stage` into the index too. Refuses, reporting `uncommitted`,
+    /// when the file has uncommitted changes unless `force` is set.
+    pub fn restore_file(
+        &self,
+        file_path: &str,
+        hash: &str,
+        stage: bool,
+        force: bool,
+    ) -> Result<RestoreResult, AppError> {
+        let content = self.show_file(hash, file_path)?;
+        let full_path = self.repo.workdir().unwrap_or(Path::new(".")).join(file_path);
+        let current = std::fs::read_to_string(&full_path).ok();
+
+        let status = self
+            .repo
+            .status_file(Path::new(file_path))
+            .unwrap_or(git2::Status::CURRENT);
+        let uncommitted = !(status - git2::Status::IGNORED).is_empty();
+        let changes = Self::file_diff(file_path, None, current.as_deref(), Some(&content));
+
+        if uncommitted && !force {
+            return Ok(RestoreResult {
+                restored: false,
+                uncommitted,
+                staged: false,
+                changes,
+            });
+        }
+
+        if let Some(parent) = full_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&full_path, &content)?;
+        if stage {
+            let mut index = self.repo.index()?;
+            index.add_path(Path::new(file_path))?;
+            index.write()?;
+        }
+
+        Ok(RestoreResult {
+            restored: true,
+            uncommitted,
+            staged: stage,
+            changes,
+        })
+    }
+
+    /// Copy selected rows and cells of `file_path` from commit or branch
+    /// `source` into the working file, matching rows by the file's merge
+    /// rule key columns. A missing working file is created with the picked
+    /// rows. Returns the diff of what was applied; nothing is staged.
+    pub fn pick_cells(
+        &self,
+        source: &str,
+        file_path: &str,
+        rows: &[String],
+        cells: &[CellRef],
+    ) -> Result<FileDiff, AppError> {
+        let full_path = self.repo.workdir().unwrap_or(Path::new(".")).join(file_path);
+        let source_text = self.show_file(source, file_path)?;
+        let theirs = Table::parse(&source_text, file_path)?;
+        let existing = if full_path.exists() {
+            let text = std::fs::read_to_string(&full_path)
+                .map_err(|_| AppError::FileNotFound(file_path.to_string()))?;
+            Some(text)
+        } else {
+            None
+        };
+        // Without a working file, start from the source's header row
+        let current = match &existing {
+            Some(text) => Table::parse(text, file_path)?,
+            None => Table {
+                headers: theirs.headers.clone(),
+                rows: Vec::new(),
+                delimiter: theirs.delimiter,
+            },
+        };
+
+        let rules = self.merge_strategies()?;
+        let key_columns = merge_strategy::rule_for(&rules, file_path)
+            .map(|rule| rule.key_columns.clone())
+            .unwrap_or_default();
+        let picked = table_diff::pick_cells(&current, &theirs, rows, cells, &key_columns)?;
+        if picked != current || existing.is_none() {
+            // Keep the file's line endings so only the picked lines change
+            let original = existing.as_deref().unwrap_or(&source_text);
+            if let Some(parent) = full_path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&full_path, picked.to_csv_like(original)?)?;
+        }
+
+        let (change, old) = match existing {
+            Some(_) => (FileChangeKind::Modified, current),
+            None => (FileChangeKind::Added, Table::empty(theirs.delimiter)),
+        };
+        Ok(FileDiff {
+            path: file_path.to_string(),
+            old_path: None,
+            change,
+            table: Some(table_diff::diff_tables(&old, &picked, &key_columns)),
+        })
+    }
+
     /// List all branches.
     pub fn branches(&self) -> Result<BranchList, AppError> {
         let current = self.current_branch()?;
         let mut branches = Vec::new();
+        let mut local = Vec::new();
+        let mut remote = Vec::new();
 
         for branch_result in self.repo.branches(Some(BranchType::Local))? {
             let (branch, _) = branch_result?;
             if let Some(name) = branch.name()? {
                 branches.push(name.to_string());
+                local.push(self.branch_info(&branch, name)?);
+            }
+        }
+
+        for branch_result in self.repo.branches(Some(BranchType::Remote))? {
+            let (branch, _) = branch_result?;
+            // Skip symbolic refs such as origin/HEAD
+            if branch.get().symbolic_target().is_some() {
+                continue;
+            }
+            if let Some(name) = branch.name()? {
+                remote.push(self.branch_info(&branch, name)?);
             }
         }
 
-        Ok(BranchList { branches, current })
+        Ok(BranchList {
+            branches,
+            current,
+            local,
+            remote,
+        })
+    }
+
+    /// Delete a local branch. Refuses to delete the current branch, and
+    /// unless `force` is set, a branch whose commits are reachable from
+    /// neither HEAD nor its upstream.
+    pub fn delete_branch(&self, name: &str, force: bool) -> Result<(), AppError> {
+        let mut branch = self.repo.find_branch(name, BranchType::Local)?;
+        if branch.is_head() {
+            return Err(AppError::GitError(git2::Error::from_str(&format!(
+                "Cannot delete the checked-out branch {}",
+                name
+            ))));
+        }
+
+        if !force {
+            let tip = branch.get().peel_to_commit()?.id();
+            let mut merged_into = Vec::new();
+            if let Ok(head) = self.repo.head() {
+                merged_into.extend(head.target());
+            }
+            if let Ok(upstream) = branch.upstream() {
+                merged_into.extend(upstream.get().target());
+            }
+            let mut merged = false;
+            for oid in merged_into {
+                if oid == tip || self.repo.graph_descendant_of(oid, tip)? {
+                    merged = true;
+                    break;
+                }
+            }
+            if !merged {
+                return Err(AppError::UnmergedBranch(name.to_string()));
+            }
+        }
+
+        branch.delete()?;
+        Ok(())
+    }
+
+    /// Rename a local branch, keeping its upstream configuration.
+    pub fn rename_branch(&self, name: &str, new_name: &str) -> Result<(), AppError> {
+        let mut branch = self.repo.find_branch(name, BranchType::Local)?;
+        branch.rename(new_name, false)?;
+        Ok(())
     }
 
-    /// Create a new branch, optionally from a specific base branch.
+    /// Create a local branch from a remote-tracking branch (e.g. `origin/feature`)
+    /// and set it as the upstream. The local name defaults to the remote
+    /// branch name without the remote prefix.
+    pub fn track_branch(&self, remote_branch: &str, name: Option<&str>) -> Result<String, AppError> {
+        let remote = self.repo.find_branch(remote_branch, BranchType::Remote)?;
+        let commit = remote.get().peel_to_commit()?;
+        let name = match name {
+            Some(name) => name.to_string(),
+            None => remote_branch
+                .split_once('/')
+                .map(|(_, branch)| branch.to_string())
+                .unwrap_or_else(|| remote_branch.to_string()),
+        };
+
+        let mut branch = self.repo.branch(&name, &commit, false)?;
+        branch.set_upstream(Some(remote_branch))?;
+        Ok(name)
+    }
+
+    /// Set a local branch's upstream to a remote-tracking branch, or unset it with `None`.
+    pub fn set_upstream(&self, name: &str, upstream: Option<&str>) -> Result<(), AppError> {
+        let mut branch = self.repo.find_branch(name, BranchType::Local)?;
+        branch.set_upstream(upstream)?;
+        Ok(())
+    }
+
+    /// Create a new branch, optionally from a specific base branch, tag or
+    /// commit.
     pub fn create_branch(&self, name: &str, from: Option<&str>) -> Result<(), AppError> {
         let target_commit = if let Some(base) = from {
-            let branch = self.repo.find_branch(base, BranchType::Local)?;
-            branch.get().peel_to_commit()?
+            match self.repo.find_branch(base, BranchType::Local) {
+                Ok(branch) => branch.get().peel_to_commit()?,
+                Err(_) => self.repo.revparse_single(base)?.peel_to_commit()?,
+            }
         } else {
             let head = self.repo.head()?;
             let oid = head.target().ok_or_else(|| {
@@ -243,29 +699,179 @@ impl GitService {
         Ok(())
     }
 
+    /// List tags, newest tagged commit first.
+    pub fn tags(&self) -> Result<Vec<TagInfo>, AppError> {
+        let mut tags = Vec::new();
+        for name in self.repo.tag_names(None)?.iter().flatten() {
+            tags.push(self.tag_info(name)?);
+        }
+        tags.sort_by(|a, b| b.commit.timestamp.cmp(&a.commit.timestamp));
+        Ok(tags)
+    }
+
+    /// Create an annotated tag at `target` (default HEAD).
+    pub fn create_tag(
+        &self,
+        name: &str,
+        target: Option<&str>,
+        message: &str,
+    ) -> Result<TagInfo, AppError> {
+        let target = self.repo.revparse_single(target.unwrap_or("HEAD"))?.peel_to_commit()?;
+        let sig = Self::default_signature(&self.repo)?;
+        self.repo.tag(name, target.as_object(), &sig, message, false)?;
+        self.tag_info(name)
+    }
+
+    /// Delete a local tag.
+    pub fn delete_tag(&self, name: &str) -> Result<(), AppError> {
+        self.repo.tag_delete(name)?;
+        Ok(())
+    }
+
+    /// Write the tables at `tag` to a `.zip` or `.tar.gz` archive at `dest`,
+    /// under a folder named after the tag, with `manifest.json` (paths, row
+    /// counts, column schemas, SHA-256 checksums) and `CHANGELOG.md` covering
+    /// the table changes since the previous tag in its history.
+    pub fn export_release(&self, tag: &str, dest: &Path) -> Result<ReleaseManifest, AppError> {
+        let format = ArchiveFormat::from_path(dest)?;
+        let commit = self
+            .repo
+            .revparse_single(&format!("refs/tags/{}", tag))?
+            .peel_to_commit()?;
+        let tree = commit.tree()?;
+
+        let mut paths: Vec<(String, git2::Oid)> = Self::tree_blobs(&tree)?
+            .into_iter()
+            .filter(|(path, _)| table_diff::is_table_path(path))
+            .collect();
+        paths.sort();
+
+        let mut entries = Vec::new();
+        let mut files = Vec::new();
+        for (path, oid) in paths {
+            let bytes = self.repo.find_blob(oid)?.content().to_vec();
+            let table = Table::parse(&String::from_utf8_lossy(&bytes), &path)?;
+            files.push(ManifestFile {
+                path: path.clone(),
+                rows: table.rows.len(),
+                columns: file_service::infer_columns(&table.headers, &table.rows),
+                sha256: release::sha256_hex(&bytes),
+            });
+            entries.push((format!("{}/{}", tag, path), bytes));
+        }
+
+        // The newest tag on an earlier commit in this tag's history
+        let mut previous: Option<(String, git2::Commit)> = None;
+        for name in self.repo.tag_names(None)?.iter().flatten() {
+            let Ok(candidate) = self
+                .repo
+                .revparse_single(&format!("refs/tags/{}", name))
+                .and_then(|object| object.peel_to_commit())
+            else {
+                continue;
+            };
+            if candidate.id() == commit.id()
+                || !self.repo.graph_descendant_of(commit.id(), candidate.id())?
+            {
+                continue;
+            }
+            if previous.as_ref().is_none_or(|(_, p)| candidate.time() > p.time()) {
+                previous = Some((name.to_string(), candidate));
+            }
+        }
+        let previous_tree = match &previous {
+            Some((_, previous)) => Some(previous.tree()?),
+            None => None,
+        };
+        let changelog = self
+            .diff_trees(previous_tree.as_ref(), Some(&tree))?
+            .into_iter()
+            .filter(|file| table_diff::is_table_path(&file.path))
+            .collect();
+
+        let manifest = ReleaseManifest {
+            tag: tag.to_string(),
+            commit: Self::commit_to_model(&commit),
+            previous_tag: previous.map(|(name, _)| name),
+            files,
+            changelog,
+        };
+        let json = serde_json::to_vec_pretty(&manifest)
+            .map_err(|e| AppError::IoError(std::io::Error::other(e)))?;
+        entries.push((format!("{}/manifest.json", tag), json));
+        entries.push((
+            format!("{}/CHANGELOG.md", tag),
+            release::changelog_markdown(&manifest).into_bytes(),
+        ));
+        release::write_archive(dest, format, &entries, commit.time().seconds())?;
+
+        Ok(manifest)
+    }
+
     /// Checkout an existing branch.
-    pub fn checkout(&self, branch: &str) -> Result<(), AppError> {
+    ///
+    /// Refuses, listing the affected files, when uncommitted changes would be
+    /// overwritten. With `auto_stash` those changes are stashed instead, the
+    /// branch is switched, and the stash is re-applied on top. If re-applying
+    /// fails, the switch still stands and the stash entry is kept.
+    pub fn checkout(&mut self, branch: &str, auto_stash: bool) -> Result<CheckoutResult, AppError> {
         let refname = format!("refs/heads/{}", branch);
-        let obj = self
-            .repo
-            .revparse_single(&refname)?;
+        let target = self.repo.revparse_single(&refname)?.id();
 
-        self.repo
-            .checkout_tree(&obj, Some(CheckoutBuilder::new().force()))?;
-        self.repo.set_head(&refname)?;
+        let blocked = self.checkout_blockers(&self.repo.find_object(target, None)?)?;
+        if !blocked.is_empty() && !auto_stash {
+            return Ok(CheckoutResult {
+                switched: false,
+                blocked,
+                stashed: false,
+                conflicts: Vec::new(),
+                stash_kept: false,
+                stash_error: None,
+            });
+        }
 
-        Ok(())
+        let stashed = !blocked.is_empty();
+        if stashed {
+            let message = format!("Ledgit auto-stash before checkout of {}", branch);
+            self.stash_push(&message, &[])?;
+        }
+
+        {
+            let obj = self.repo.find_object(target, None)?;
+            self.repo
+                .checkout_tree(&obj, Some(CheckoutBuilder::new().safe()))?;
+            self.repo.set_head(&refname)?;
+        }
+
+        let (conflicts, stash_error) = if !stashed {
+            (Vec::new(), None)
+        } else {
+            match self.stash_restore(0, true) {
+                Ok(conflicts) => (conflicts, None),
+                Err(err) => (Vec::new(), Some(err.to_string())),
+            }
+        };
+
+        Ok(CheckoutResult {
+            switched: true,
+            blocked,
+            stashed,
+            stash_kept: stashed && (!conflicts.is_empty() || stash_error.is_some()),
+            conflicts,
+            stash_error,
+        })
     }
 
     /// Merge a source branch into the current branch.
     /// Handles fast-forward, normal merge, and conflicts.
     pub fn merge(&self, source: &str) -> Result<MergeResult, AppError> {
-        let source_ref = format!("refs/heads/{}", source);
-        let annotated = self
-            .repo
-            .find_reference(&source_ref)?
-            .peel_to_commit()?;
-        let annotated_commit = self.repo.find_annotated_commit(annotated.id())?;
+        // Annotate from the reference (not the bare OID) so MERGE_MSG records the branch name
+        let reference = match self.repo.find_reference(&format!("refs/heads/{}", source)) {
+            Ok(reference) => reference,
+            // Remote-tracking branches, e.g. fetched from a bundle
+            Err(_) => self.repo.find_reference(&format!("refs/remotes/{}", source))?,
+        };
+        let annotated_commit = self.repo.reference_to_annotated_commit(&reference)?;
 
         let (analysis, _preference) = self.repo.merge_analysis(&[&annotated_commit])?;
 
@@ -300,17 +906,11 @@ impl GitService {
         self.repo
             .merge(&[&annotated_commit], Some(&mut merge_opts), None)?;
 
-        // Check for conflicts
-        let index = self.repo.index()?;
-        if index.has_conflicts() {
-            let mut conflict_files = Vec::new();
-            for conflict in index.conflicts()? {
-                let conflict = conflict?;
-                if let Some(ancestor) = conflict.our {
-                    let path = String::from_utf8_lossy(&ancestor.path).to_string();
-                    conflict_files.push(path);
-                }
-            }
+        // Settle files with merge strategies / cell-level merge, then check for conflicts
+        let source_commit = self.repo.find_commit(annotated_commit.id())?;
+        let their_branch = self.owner_branch_name(source);
+        let conflict_files = self.settle_merge(&source_commit, Some(&their_branch))?;
+        if !conflict_files.is_empty() {
             return Ok(MergeResult {
                 success: false,
                 conflicts: Some(conflict_files),
@@ -324,7 +924,6 @@ impl GitService {
         let sig = Self::default_signature(&self.repo)?;
 
         let head_commit = self.repo.head()?.peel_to_commit()?;
-        let source_commit = self.repo.find_commit(annotated_commit.id())?;
 
         let current_branch = self.current_branch()?;
         let msg = format!("Merge branch '{}' into '{}'", source, current_branch);
@@ -347,95 +946,479 @@ impl GitService {
         })
     }
 
-    /// Push to a remote.
-    pub fn push(&self, remote_name: &str, branch: &str) -> Result<(), AppError> {
-        let mut remote = self.repo.find_remote(remote_name)?;
-        let refspec = format!("refs/heads/{}:refs/heads/{}", branch, branch);
-        remote.push(&[&refspec], None)?;
-        Ok(())
-    }
+    /// Preview merging `source` into the current branch. The merge runs in
+    /// memory only; the index, working tree and refs are left untouched.
+    pub fn merge_preview(&self, source: &str) -> Result<MergePreview, AppError> {
+        let source_commit = self.repo.revparse_single(source)?.peel_to_commit()?;
+        let head_commit = self.repo.head()?.peel_to_commit()?;
+        let annotated = self.repo.find_annotated_commit(source_commit.id())?;
+        let (analysis, _) = self.repo.merge_analysis(&[&annotated])?;
 
-    /// Pull from a remote (fetch + merge).
-    pub fn pull(&self, remote_name: &str, branch: &str) -> Result<PullResult, AppError> {
-        // Remember HEAD before pull to count new commits afterwards
-        let head_oid_before = self.repo.head().ok().and_then(|h| h.target());
+        let mut preview = MergePreview {
+            source: source.to_string(),
+            up_to_date: analysis.is_up_to_date(),
+            fast_forward: analysis.is_fast_forward(),
+            files: Vec::new(),
+            conflicts: Vec::new(),
+        };
 
-        // Fetch
-        let mut remote = self.repo.find_remote(remote_name)?;
-        remote.fetch(&[branch], None, None)?;
+        if preview.up_to_date {
+            return Ok(preview);
+        }
 
-        // Find the fetched commit
-        let fetch_head = self
-            .repo
-            .find_reference(&format!("refs/remotes/{}/{}", remote_name, branch))?;
-        let fetch_commit = fetch_head.peel_to_commit()?;
-        let annotated = self.repo.find_annotated_commit(fetch_commit.id())?;
+        let head_tree = head_commit.tree()?;
+        if preview.fast_forward {
+            preview.files = self.diff_trees(Some(&head_tree), Some(&source_commit.tree()?))?;
+            return Ok(preview);
+        }
 
-        let (analysis, _) = self.repo.merge_analysis(&[&annotated])?;
+        let source_tree = source_commit.tree()?;
+        let mut index = self.repo.merge_commits(&head_commit, &source_commit, None)?;
+        let ancestor_tree = self.merge_base_tree(head_commit.id(), source_commit.id())?;
+        let rules = self.merge_strategies()?;
+        let our_branch = self.current_branch().ok();
+        let their_branch = self.owner_branch_name(source);
 
-        if analysis.is_up_to_date() {
-            return Ok(PullResult {
-                updated: false,
-                new_commits: 0,
-                conflicts: None,
-            });
+        // Files both sides changed: textual conflicts plus files with a strategy rule
+        let mut candidates = Vec::new();
+        for file in self.index_conflicts(&index)? {
+            candidates.push((file, true));
+        }
+        let conflicted: HashSet<String> =
+            candidates.iter().flat_map(|(f, _)| Self::side_paths(f)).collect();
+        for file in self.strategy_candidates(
+            ancestor_tree.as_ref(),
+            &head_tree,
+            &source_tree,
+            &rules,
+            &conflicted,
+        )? {
+            candidates.push((file, false));
         }
 
-        if analysis.is_fast_forward() {
-            let target_oid = annotated.id();
-            let head_ref = self.repo.head()?;
-            if let Some(name) = head_ref.name() {
-                self.repo.reference(name, target_oid, true, "Fast-forward pull")?;
+        // Settle them the way `merge` would and remember the predicted content
+        let mut settled: HashMap<String, Merged> = HashMap::new();
+        for (file, textual_conflict) in candidates {
+            let rule = merge_strategy::rule_for(&rules, &file.path);
+            let resolution =
+                merge_strategy::resolve(&file, rule, our_branch.as_deref(), Some(&their_branch));
+            match resolution {
+                Some(resolution) => {
+                    if !resolution.conflicts.is_empty() {
+                        preview.conflicts.push(MergePreviewConflict {
+                            path: file.path.clone(),
+                            kind: file.kind,
+                            cells: resolution.conflicts,
+                        });
+                    }
+                    settled.insert(file.path, resolution.merged);
+                }
+                None if textual_conflict => {
+                    preview.conflicts.push(MergePreviewConflict {
+                        path: file.path.clone(),
+                        kind: file.kind,
+                        cells: Vec::new(),
+                    });
+                    // Predict ours for now; the other paths of a rename go away
+                    for path in Self::side_paths(&file) {
+                        settled.insert(path, Merged::Deleted);
+                    }
+                    if let Some(ours) = &file.ours {
+                        if let Some(taken) = merge_strategy::take_side(&file.ours) {
+                            settled.insert(ours.path.clone(), taken.merged);
+                        }
+                    }
+                }
+                None => {}
             }
-            let obj = self.repo.find_object(target_oid, None)?;
-            self.repo
-                .checkout_tree(&obj, Some(CheckoutBuilder::new().force()))?;
-            self.repo.set_head(head_ref.name().unwrap_or("refs/heads/main"))?;
+        }
 
-            let new_commits = Self::count_commits_between(&self.repo, head_oid_before, target_oid);
-            return Ok(PullResult {
-                updated: true,
-                new_commits,
-                conflicts: None,
-            });
+        // Build the predicted tree: cleanly merged entries plus the settled
+        // files, so it diffs against HEAD (renames included) like a fast-forward
+        for path in settled.keys() {
+            index.remove_path(Path::new(path))?;
         }
+        let merged = self.repo.find_tree(index.write_tree_to(&self.repo)?)?;
+        let mut update = git2::build::TreeUpdateBuilder::new();
+        for (path, merged) in &settled {
+            let blob = match merged {
+                Merged::Text(content) => self.repo.blob(content.as_bytes())?,
+                Merged::Blob(oid) => *oid,
+                Merged::Deleted => continue,
+            };
+            update.upsert(path.as_str(), blob, git2::FileMode::Blob);
+        }
+        let predicted = self.repo.find_tree(update.create_updated(&self.repo, &merged)?)?;
+        preview.files = self.diff_trees(Some(&head_tree), Some(&predicted))?;
 
-        // Normal merge
-        let mut merge_opts = MergeOptions::new();
-        self.repo.merge(&[&annotated], Some(&mut merge_opts), None)?;
+        Ok(preview)
+    }
 
-        let index = self.repo.index()?;
-        if index.has_conflicts() {
-            let mut conflict_files = Vec::new();
-            for conflict in index.conflicts()? {
-                let conflict = conflict?;
-                if let Some(our) = conflict.our {
-                    let path = String::from_utf8_lossy(&our.path).to_string();
-                    conflict_files.push(path);
+    /// List the per-file merge strategy rules from the repository config,
+    /// in the order they are tried.
+    pub fn merge_strategies(&self) -> Result<Vec<MergeStrategyRule>, AppError> {
+        let config = self.repo.config()?.snapshot()?;
+        let prefix = format!("{}.", merge_strategy::CONFIG_SECTION);
+        let mut rules: Vec<MergeStrategyRule> = Vec::new();
+
+        let mut entries =
+            config.entries(Some(&format!("{}\\..*", merge_strategy::CONFIG_SECTION)))?;
+        while let Some(entry) = entries.next() {
+            let entry = entry?;
+            let (Some(name), Some(value)) = (entry.name(), entry.value()) else { continue };
+            let Some((pattern, variable)) = name
+                .strip_prefix(&prefix)
+                .and_then(|rest| rest.rsplit_once('.'))
+            else {
+                continue;
+            };
+
+            let index = match rules.iter().position(|r| r.pattern == pattern) {
+                Some(index) => index,
+                None => {
+                    rules.push(MergeStrategyRule {
+                        pattern: pattern.to_string(),
+                        strategy: MergeStrategy::default(),
+                        key_columns: Vec::new(),
+                        column_owners: Vec::new(),
+                    });
+                    rules.len() - 1
                 }
+            };
+            let rule = &mut rules[index];
+
+            match variable {
+                "strategy" => {
+                    rule.strategy = merge_strategy::parse_strategy(value).unwrap_or_default();
+                }
+                "key" => rule.key_columns = split_list(value),
+                "owner" => {
+                    if let Some((branch, columns)) = value.split_once(':') {
+                        rule.column_owners.push(ColumnOwner {
+                            branch: branch.trim().to_string(),
+                            columns: split_list(columns),
+                        });
+                    }
+                }
+                _ => {}
             }
-            return Ok(PullResult {
-                updated: false,
-                new_commits: 0,
-                conflicts: Some(conflict_files),
-            });
         }
 
-        // Create merge commit
-        let mut index = self.repo.index()?;
-        let tree_oid = index.write_tree()?;
-        let tree = self.repo.find_tree(tree_oid)?;
-        let sig = Self::default_signature(&self.repo)?;
-        let head_commit = self.repo.head()?.peel_to_commit()?;
-        let remote_commit = self.repo.find_commit(annotated.id())?;
+        Ok(rules)
+    }
 
-        self.repo.commit(
-            Some("HEAD"),
-            &sig,
-            &sig,
-            &format!("Merge remote branch '{}/{}'", remote_name, branch),
-            &tree,
-            &[&head_commit, &remote_commit],
+    /// Add or replace the merge strategy rule for a file pattern. A replaced
+    /// rule keeps its place, since the first matching rule wins.
+    pub fn set_merge_strategy(&self, rule: &MergeStrategyRule) -> Result<(), AppError> {
+        let mut config = self.repo.config()?.open_level(ConfigLevel::Local)?;
+
+        // Keys are updated in place so the section isn't moved to the end
+        let prefix = format!("{}.{}", merge_strategy::CONFIG_SECTION, rule.pattern);
+        config.set_str(
+            &format!("{}.strategy", prefix),
+            merge_strategy::strategy_name(rule.strategy),
+        )?;
+        let key = format!("{}.key", prefix);
+        if rule.key_columns.is_empty() {
+            match config.remove(&key) {
+                Err(e) if e.code() != git2::ErrorCode::NotFound => return Err(e.into()),
+                _ => {}
+            }
+        } else {
+            config.set_str(&key, &rule.key_columns.join(","))?;
+        }
+        match config.remove_multivar(&format!("{}.owner", prefix), ".*") {
+            Err(e) if e.code() != git2::ErrorCode::NotFound => return Err(e.into()),
+            _ => {}
+        }
+        for owner in &rule.column_owners {
+            let value = format!("{}:{}", owner.branch, owner.columns.join(","));
+            // A regex matching no existing value appends a new entry
+            config.set_multivar(&format!("{}.owner", prefix), "^$", &value)?;
+        }
+
+        Ok(())
+    }
+
+    /// Remove the merge strategy rule for a file pattern.
+    pub fn remove_merge_strategy(&self, pattern: &str) -> Result<(), AppError> {
+        let mut config = self.repo.config()?.open_level(ConfigLevel::Local)?;
+        Self::clear_strategy_keys(&mut config, pattern)
+    }
+
+    /// Stash uncommitted changes (all of them, or only `files`) and return the new entry.
+    pub fn stash_save(&mut self, message: &str, files: &[String]) -> Result<StashEntry, AppError> {
+        self.stash_push(message, files)?;
+        self.stash_list()?
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::GitError(git2::Error::from_str("Stash was not created")))
+    }
+
+    /// List stash entries, newest first, each with a table-diff summary of
+    /// what it holds relative to the commit it was made on.
+    pub fn stash_list(&self) -> Result<Vec<StashEntry>, AppError> {
+        let reflog = self.repo.reflog("refs/stash")?;
+        let mut entries = Vec::new();
+
+        for (index, entry) in reflog.iter().enumerate() {
+            let commit = self.repo.find_commit(entry.id_new())?;
+            let base = commit.parent(0)?;
+            let mut files = self.diff_trees(Some(&base.tree()?), Some(&commit.tree()?))?;
+            // Untracked files are stored in a third parent
+            if commit.parent_count() > 2 {
+                files.extend(self.diff_trees(None, Some(&commit.parent(2)?.tree()?))?);
+            }
+
+            entries.push(StashEntry {
+                index,
+                message: entry.message().unwrap_or("").to_string(),
+                hash: commit.id().to_string(),
+                base: base.id().to_string(),
+                timestamp: Self::format_timestamp(commit.time().seconds()),
+                files,
+            });
+        }
+
+        Ok(entries)
+    }
+
+    /// Apply a stash entry, keeping it in the stash list.
+    pub fn stash_apply(&mut self, index: usize) -> Result<MergeResult, AppError> {
+        let conflicts = self.stash_restore(index, false)?;
+        Ok(Self::stash_merge_result(conflicts))
+    }
+
+    /// Apply a stash entry and drop it, unless applying left conflicts.
+    pub fn stash_pop(&mut self, index: usize) -> Result<MergeResult, AppError> {
+        let conflicts = self.stash_restore(index, true)?;
+        Ok(Self::stash_merge_result(conflicts))
+    }
+
+    /// Delete a stash entry.
+    pub fn stash_drop(&mut self, index: usize) -> Result<(), AppError> {
+        self.repo.stash_drop(index)?;
+        Ok(())
+    }
+
+    /// Push a branch to a remote, reporting each ref's outcome. Fails with
+    /// `PushRejected` when the remote refuses a ref, e.g. a non-fast-forward.
+    ///
+    /// With `force_with_lease` set to the remote branch's expected commit, the
+    /// push is forced, but only after checking the remote branch is still at
+    /// that commit. libgit2 has no atomic lease, so this narrows rather than
+    /// closes the window for overwriting someone else's push.
+    pub fn push(
+        &self,
+        remote_name: &str,
+        branch: &str,
+        force_with_lease: Option<&str>,
+        auth: &RemoteAuth,
+        cancel: &AtomicBool,
+        progress: &mut dyn FnMut(TransferProgress),
+    ) -> Result<PushResult, AppError> {
+        let refname = format!("refs/heads/{}", branch);
+        self.push_refname(remote_name, &refname, force_with_lease, auth, cancel, progress)
+    }
+
+    /// Push an annotated or lightweight tag to a remote.
+    pub fn push_tag(
+        &self,
+        remote_name: &str,
+        name: &str,
+        auth: &RemoteAuth,
+        cancel: &AtomicBool,
+        progress: &mut dyn FnMut(TransferProgress),
+    ) -> Result<PushResult, AppError> {
+        let refname = format!("refs/tags/{}", name);
+        self.repo.find_reference(&refname)?;
+        self.push_refname(remote_name, &refname, None, auth, cancel, progress)
+    }
+
+    /// Push one ref to every push URL of a remote; see `push`.
+    fn push_refname(
+        &self,
+        remote_name: &str,
+        refname: &str,
+        force_with_lease: Option<&str>,
+        auth: &RemoteAuth,
+        cancel: &AtomicBool,
+        progress: &mut dyn FnMut(TransferProgress),
+    ) -> Result<PushResult, AppError> {
+        let push_urls = self.push_urls(remote_name)?;
+        let force = force_with_lease.is_some();
+
+        if push_urls.len() <= 1 {
+            let mut remote = self.repo.find_remote(remote_name)?;
+            let url = remote.url().unwrap_or(remote_name).to_string();
+            let transfer = Transfer::new(&url, auth, cancel, progress);
+            if let Some(expected) = force_with_lease {
+                self.check_lease(&mut remote, &transfer, refname, expected)?;
+            }
+            return Ok(PushResult {
+                remote: remote_name.to_string(),
+                refs: self.push_ref(&mut remote, &transfer, remote_name, refname, force)?,
+                failed_urls: Vec::new(),
+            });
+        }
+
+        // libgit2 only pushes to one URL, so push to each like git does. Every
+        // lease is checked before anything is pushed
+        if let Some(expected) = force_with_lease {
+            for url in &push_urls {
+                let mut remote = self.repo.remote_anonymous(url)?;
+                let transfer = Transfer::new(url, auth, cancel, &mut *progress);
+                self.check_lease(&mut remote, &transfer, refname, expected)?;
+            }
+        }
+
+        // A failing URL doesn't stop the others; it is reported instead
+        let mut refs = Vec::new();
+        let mut failed = Vec::new();
+        for url in &push_urls {
+            let mut remote = self.repo.remote_anonymous(url)?;
+            let transfer = Transfer::new(url, auth, cancel, &mut *progress);
+            match self.push_ref(&mut remote, &transfer, url, refname, force) {
+                Ok(pushed) => refs.extend(pushed),
+                Err(AppError::Cancelled) => return Err(AppError::Cancelled),
+                Err(err) => failed.push((url.clone(), err)),
+            }
+        }
+        if refs.is_empty() && !failed.is_empty() {
+            return Err(failed.swap_remove(0).1);
+        }
+
+        // Anonymous remotes don't update tracking refs
+        let remote = self.repo.find_remote(remote_name)?;
+        let tip = self.repo.refname_to_id(refname)?;
+        for spec in remote.refspecs().filter(|s| s.direction() == Direction::Fetch) {
+            if spec.src_matches(refname) {
+                if let Some(tracking) = spec.transform(refname)?.as_str() {
+                    self.repo.reference(tracking, tip, true, "push")?;
+                }
+            }
+        }
+
+        Ok(PushResult {
+            remote: remote_name.to_string(),
+            refs,
+            failed_urls: failed
+                .into_iter()
+                .map(|(url, err)| PushUrlError {
+                    url,
+                    error: err.to_string(),
+                })
+                .collect(),
+        })
+    }
+
+    /// Pull from a remote (fetch + merge). With `rebase`, local commits are
+    /// instead replayed on top of the fetched branch; see `rebase_continue`.
+    /// The fetch reports progress and stops early once `cancel` is set.
+    pub fn pull(
+        &self,
+        remote_name: &str,
+        branch: &str,
+        rebase: bool,
+        auth: &RemoteAuth,
+        cancel: &AtomicBool,
+        progress: &mut dyn FnMut(TransferProgress),
+    ) -> Result<PullResult, AppError> {
+        // Remember HEAD before pull to count new commits afterwards
+        let head_oid_before = self.repo.head().ok().and_then(|h| h.target());
+
+        // Fetch
+        let mut remote = self.repo.find_remote(remote_name)?;
+        let url = remote.url().unwrap_or(remote_name).to_string();
+        let transfer = Transfer::new(&url, auth, cancel, progress);
+        let mut fetch_opts = FetchOptions::new();
+        fetch_opts.remote_callbacks(transfer.callbacks());
+        remote
+            .fetch(&[branch], Some(&mut fetch_opts), None)
+            .map_err(|err| transfer.map_error(err))?;
+
+        // Find the fetched commit
+        let fetch_head = self
+            .repo
+            .find_reference(&format!("refs/remotes/{}/{}", remote_name, branch))?;
+        let annotated = self.repo.reference_to_annotated_commit(&fetch_head)?;
+
+        let (analysis, _) = self.repo.merge_analysis(&[&annotated])?;
+
+        if analysis.is_up_to_date() {
+            return Ok(PullResult {
+                updated: false,
+                new_commits: 0,
+                conflicts: None,
+                rebase: None,
+            });
+        }
+
+        if analysis.is_fast_forward() {
+            let target_oid = annotated.id();
+            let head_ref = self.repo.head()?;
+            if let Some(name) = head_ref.name() {
+                self.repo.reference(name, target_oid, true, "Fast-forward pull")?;
+            }
+            let obj = self.repo.find_object(target_oid, None)?;
+            self.repo
+                .checkout_tree(&obj, Some(CheckoutBuilder::new().force()))?;
+            self.repo.set_head(head_ref.name().unwrap_or("refs/heads/main"))?;
+
+            let new_commits = Self::count_commits_between(&self.repo, head_oid_before, target_oid);
+            return Ok(PullResult {
+                updated: true,
+                new_commits,
+                conflicts: None,
+                rebase: None,
+            });
+        }
+
+        if rebase {
+            let incoming = match head_oid_before {
+                Some(head) => self.repo.graph_ahead_behind(annotated.id(), head)?.0,
+                None => 0,
+            };
+            let mut rebase = self.repo.rebase(None, Some(&annotated), None, None)?;
+            let result = self.run_rebase(&mut rebase)?;
+            return Ok(PullResult {
+                updated: result.finished,
+                new_commits: incoming as u32,
+                conflicts: (!result.conflicts.is_empty()).then(|| result.conflicts.clone()),
+                rebase: Some(result),
+            });
+        }
+
+        // Normal merge
+        let mut merge_opts = MergeOptions::new();
+        self.repo.merge(&[&annotated], Some(&mut merge_opts), None)?;
+
+        // Column owners name the branch, not its remote-tracking ref
+        let remote_commit = self.repo.find_commit(annotated.id())?;
+        let conflict_files = self.settle_merge(&remote_commit, Some(branch))?;
+        if !conflict_files.is_empty() {
+            return Ok(PullResult {
+                updated: false,
+                new_commits: 0,
+                conflicts: Some(conflict_files),
+                rebase: None,
+            });
+        }
+
+        // Create merge commit
+        let mut index = self.repo.index()?;
+        let tree_oid = index.write_tree()?;
+        let tree = self.repo.find_tree(tree_oid)?;
+        let sig = Self::default_signature(&self.repo)?;
+        let head_commit = self.repo.head()?.peel_to_commit()?;
+
+        self.repo.commit(
+            Some("HEAD"),
+            &sig,
+            &sig,
+            &format!("Merge remote branch '{}/{}'", remote_name, branch),
+            &tree,
+            &[&head_commit, &remote_commit],
         )?;
 
         self.repo.cleanup_state()?;
@@ -445,6 +1428,7 @@ impl GitService {
             updated: true,
             new_commits,
             conflicts: None,
+            rebase: None,
         })
     }
 
@@ -454,6 +1438,41 @@ impl GitService {
         Ok(())
     }
 
+    /// Remove a remote along with its remote-tracking branches.
+    pub fn remove_remote(&self, name: &str) -> Result<(), AppError> {
+        self.repo.remote_delete(name)?;
+        Ok(())
+    }
+
+    /// Rename a remote, moving its tracking branches. Returns any custom
+    /// fetch refspecs that could not be rewritten and need updating by hand.
+    pub fn rename_remote(&self, name: &str, new_name: &str) -> Result<Vec<String>, AppError> {
+        let problems = self.repo.remote_rename(name, new_name)?;
+        Ok(problems.iter().flatten().map(str::to_string).collect())
+    }
+
+    /// Change the URL a remote fetches from (and pushes to, without push URLs).
+    pub fn set_remote_url(&self, name: &str, url: &str) -> Result<(), AppError> {
+        self.repo.find_remote(name)?;
+        self.repo.remote_set_url(name, url)?;
+        Ok(())
+    }
+
+    /// Replace a remote's push URLs. An empty list pushes to the fetch URL again.
+    pub fn set_remote_push_urls(&self, name: &str, urls: &[String]) -> Result<(), AppError> {
+        self.repo.find_remote(name)?;
+        let mut config = self.repo.config()?.open_level(ConfigLevel::Local)?;
+        let key = format!("remote.{}.pushurl", name);
+        match config.remove_multivar(&key, ".*") {
+            Err(e) if e.code() != ErrorCode::NotFound => return Err(e.into()),
+            _ => {}
+        }
+        for url in urls {
+            config.set_multivar(&key, "^$", url)?;
+        }
+        Ok(())
+    }
+
     /// List all remotes.
     pub fn remotes(&self) -> Result<Vec<Remote>, AppError> {
         let remote_names = self.repo.remotes()?;
@@ -462,9 +1481,17 @@ impl GitService {
         for name in remote_names.iter() {
             if let Some(name) = name {
                 if let Ok(remote) = self.repo.find_remote(name) {
+                    let fetch_refspecs = remote
+                        .fetch_refspecs()?
+                        .iter()
+                        .flatten()
+                        .map(str::to_string)
+                        .collect();
                     remotes.push(Remote {
                         name: name.to_string(),
                         url: remote.url().unwrap_or("").to_string(),
+                        push_urls: self.push_urls(name)?,
+                        fetch_refspecs,
                     });
                 }
             }
@@ -497,24 +1524,167 @@ impl GitService {
             .ok()
             .and_then(|r| r.url().map(|u| u.to_string()));
 
+        let tracking = match self.repo.find_branch(&branch, BranchType::Local) {
+            Ok(local) => Some(self.branch_info(&local, &branch)?),
+            Err(_) => None,
+        };
+
         Ok(RepoInfo {
             path,
             name,
             branch,
             remote_url,
+            upstream: tracking.as_ref().and_then(|t| t.upstream.clone()),
+            ahead: tracking.as_ref().map_or(0, |t| t.ahead),
+            behind: tracking.as_ref().map_or(0, |t| t.behind),
         })
     }
 
-    /// Resolve conflicts by staging resolved files and committing.
-    pub fn resolve_conflicts(&self, files: &[String]) -> Result<Commit, AppError> {
-        let mut index = self.repo.index()?;
+    /// Fetch from a remote, updating remote-tracking refs only. Without a
+    /// remote name, uses the current branch's upstream remote or `origin`.
+    pub fn fetch(
+        &self,
+        remote_name: Option<&str>,
+        auth: &RemoteAuth,
+        cancel: &AtomicBool,
+        progress: &mut dyn FnMut(TransferProgress),
+    ) -> Result<FetchResult, AppError> {
+        let remote_name = match remote_name {
+            Some(name) => name.to_string(),
+            None => self.default_remote(),
+        };
+        let mut remote = self.repo.find_remote(&remote_name)?;
+        let url = remote.url().unwrap_or(&remote_name).to_string();
+        let transfer = Transfer::new(&url, auth, cancel, progress);
 
-        // Stage the resolved files, which also clears their conflict entries
-        for file in files {
-            index.add_path(Path::new(file))?;
+        let mut fetch_opts = FetchOptions::new();
+        fetch_opts.remote_callbacks(transfer.callbacks());
+        // No refspecs: use the remote's configured fetch refspecs
+        remote
+            .fetch(&[] as &[&str], Some(&mut fetch_opts), None)
+            .map_err(|err| transfer.map_error(err))?;
+
+        let stats = remote.stats();
+        Ok(FetchResult {
+            remote: remote_name,
+            updated: transfer.updated(),
+            received_objects: stats.received_objects(),
+            received_bytes: stats.received_bytes(),
+        })
+    }
+
+    /// Write a bundle of `branches` (all local branches when empty) to
+    /// `dest`. With `since`, only commits after it are included, and the
+    /// receiver must already have it.
+    pub fn export_bundle(
+        &self,
+        dest: &Path,
+        branches: &[String],
+        since: Option<&str>,
+    ) -> Result<BundleInfo, AppError> {
+        let names = if branches.is_empty() {
+            self.branches()?.branches
+        } else {
+            branches.to_vec()
+        };
+
+        let mut walk = self.repo.revwalk()?;
+        let mut refs = Vec::new();
+        for name in &names {
+            let branch = self.repo.find_branch(name, BranchType::Local)?;
+            let tip = branch.get().peel_to_commit()?.id();
+            walk.push(tip)?;
+            refs.push((format!("refs/heads/{}", name), tip));
         }
 
-        index.write()?;
+        let mut prerequisites = Vec::new();
+        let mut comments = Vec::new();
+        if let Some(since) = since {
+            let base = self.repo.revparse_single(since)?.peel_to_commit()?;
+            walk.hide(base.id())?;
+            prerequisites.push(base.id());
+            comments.push(base.summary().unwrap_or("").to_string());
+        }
+
+        let mut builder = self.repo.packbuilder()?;
+        builder.insert_walk(&mut walk)?;
+        if builder.object_count() == 0 {
+            return Err(AppError::GitError(git2::Error::from_str(
+                "Nothing to bundle: the branches have no commits after the base",
+            )));
+        }
+        let mut pack = git2::Buf::new();
+        builder.write_buf(&mut pack)?;
+
+        let bundle = Bundle {
+            prerequisites,
+            refs,
+            pack: pack.to_vec(),
+        };
+        bundle.write(dest, &comments)?;
+
+        Ok(BundleInfo {
+            refs: bundle.refs.iter().map(|(name, _)| name.clone()).collect(),
+            prerequisites: bundle.prerequisites.iter().map(|oid| oid.to_string()).collect(),
+            objects: bundle.object_count(),
+        })
+    }
+
+    /// Fetch from a bundle file as if it were remote `remote_name`: its
+    /// branches land in `refs/remotes/<remote_name>/`, ready to merge, and
+    /// tags the repository doesn't have yet are created.
+    pub fn fetch_bundle(&self, path: &Path, remote_name: &str) -> Result<FetchResult, AppError> {
+        let bundle = Bundle::read(path)?;
+        let odb = self.repo.odb()?;
+        let missing: Vec<String> = bundle
+            .prerequisites
+            .iter()
+            .filter(|oid| !odb.exists(**oid))
+            .map(|oid| oid.to_string())
+            .collect();
+        if !missing.is_empty() {
+            return Err(AppError::MissingPrerequisites(missing));
+        }
+
+        if bundle.object_count() > 0 {
+            let mut writer = odb.packwriter()?;
+            std::io::Write::write_all(&mut writer, &bundle.pack)?;
+            writer.commit()?;
+        }
+
+        let mut updated = Vec::new();
+        let log_message = format!("fetch: from bundle {}", path.display());
+        for (name, oid) in &bundle.refs {
+            let target = if let Some(branch) = name.strip_prefix("refs/heads/") {
+                format!("refs/remotes/{}/{}", remote_name, branch)
+            } else if name.starts_with("refs/tags/") {
+                name.clone()
+            } else {
+                continue;
+            };
+            let old = self.repo.refname_to_id(&target).ok();
+            if old == Some(*oid) || (old.is_some() && target.starts_with("refs/tags/")) {
+                continue;
+            }
+            self.repo.reference(&target, *oid, true, &log_message)?;
+            updated.push(RefUpdate {
+                name: target,
+                old: old.map(|oid| oid.to_string()),
+                new: Some(oid.to_string()),
+            });
+        }
+
+        Ok(FetchResult {
+            remote: remote_name.to_string(),
+            updated,
+            received_objects: bundle.object_count(),
+            received_bytes: bundle.pack.len(),
+        })
+    }
+
+    /// Resolve conflicts by staging resolved files and committing.
+    pub fn resolve_conflicts(&self, files: &[String]) -> Result<Commit, AppError> {
+        let mut index = self.stage_resolved(files)?;
         let tree_oid = index.write_tree()?;
         let tree = self.repo.find_tree(tree_oid)?;
         let sig = Self::default_signature(&self.repo)?;
@@ -535,11 +1705,25 @@ impl GitService {
 
         let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
 
+        // A cherry-pick keeps the author of the picked commit
+        let picked = std::fs::read_to_string(self.repo.path().join("CHERRY_PICK_HEAD"))
+            .ok()
+            .and_then(|s| git2::Oid::from_str(s.trim()).ok())
+            .and_then(|oid| self.repo.find_commit(oid).ok());
+        let author = picked.map(|c| c.author().to_owned()).unwrap_or_else(|| sig.to_owned());
+
+        // A revert or cherry-pick leaves its commit message in MERGE_MSG
+        let message = match parents.len() {
+            1 => std::fs::read_to_string(self.repo.path().join("MERGE_MSG"))
+                .unwrap_or_else(|_| "Resolve merge conflicts".to_string()),
+            _ => "Resolve merge conflicts".to_string(),
+        };
+
         let oid = self.repo.commit(
             Some("HEAD"),
+            &author,
             &sig,
-            &sig,
-            "Resolve merge conflicts",
+            &message,
             &tree,
             &parent_refs,
         )?;
@@ -550,182 +1734,3296 @@ impl GitService {
         Ok(Self::commit_to_model(&commit))
     }
 
-    // ── Private helpers ──────────────────────────────────────────────
+    /// Stage files resolved during a stopped rebase and carry on replaying.
+    /// Stops again, reporting conflicts, if any remain.
+    pub fn rebase_continue(&self, files: &[String]) -> Result<RebaseResult, AppError> {
+        let mut rebase = self.open_rebase()?;
+        self.stage_resolved(files)?;
 
-    /// Get the current branch name.
-    fn current_branch(&self) -> Result<String, AppError> {
-        let head = self.repo.head()?;
-        let name = head
-            .shorthand()
-            .unwrap_or("HEAD")
-            .to_string();
-        Ok(name)
+        if let Some(current) = rebase.operation_current() {
+            let id = rebase.nth(current).map(|op| op.id());
+            let conflicts = self.conflicted_paths()?;
+            if let (Some(id), false) = (id, conflicts.is_empty()) {
+                let commit = self.repo.find_commit(id)?;
+                return Ok(Self::rebase_stopped(&mut rebase, &commit, conflicts));
+            }
+            Self::rebase_commit(&mut rebase, &Self::default_signature(&self.repo)?)?;
+        }
+
+        self.run_rebase(&mut rebase)
     }
 
-    /// Create a default signature using "Ledgit" as the author.
-    fn default_signature(repo: &Repository) -> Result<Signature<'_>, AppError> {
-        // Try to get signature from git config first
-        match repo.signature() {
-            Ok(sig) => Ok(sig),
-            Err(_) => {
-                let sig = Signature::now("Ledgit", "ledgit@local")?;
-                Ok(sig)
+    /// Abandon a rebase, restoring the branch and working tree.
+    pub fn rebase_abort(&self) -> Result<(), AppError> {
+        self.open_rebase()?.abort()?;
+        Ok(())
+    }
+
+    /// List every conflicted path in the index with the ancestor/ours/theirs
+    /// contents from the index stages, plus the in-progress merge source.
+    pub fn conflicts(&self) -> Result<ConflictList, AppError> {
+        let index = self.repo.index()?;
+        let files = self.index_conflicts(&index)?;
+
+        Ok(ConflictList {
+            source: self.merge_source_name(),
+            merge_head: self.read_state_lines("MERGE_HEAD").into_iter().next(),
+            files,
+        })
+    }
+
+    /// Abort an in-progress merge, revert or cherry-pick, restoring HEAD, the
+    /// index and the files the operation touched to their pre-merge state.
+    /// Other uncommitted changes in the working tree are kept, as with
+    /// `git merge --abort`.
+    pub fn merge_abort(&self) -> Result<(), AppError> {
+        if !matches!(
+            self.repo.state(),
+            RepositoryState::Merge | RepositoryState::Revert | RepositoryState::CherryPick
+        ) {
+            return Err(AppError::NoMergeInProgress);
+        }
+
+        // libgit2 never moves HEAD until the merge commit is made, so HEAD is the pre-merge commit
+        let head = self.repo.head()?.peel_to_commit()?;
+        let head_tree = head.tree()?;
+
+        // The operation only touched what it staged or left conflicted
+        let mut index = self.repo.index()?;
+        let mut touched = self.conflicted_paths()?;
+        let staged = self.repo.diff_tree_to_index(Some(&head_tree), Some(&index), None)?;
+        for delta in staged.deltas() {
+            for file in [delta.old_file(), delta.new_file()] {
+                if let Some(path) = file.path().and_then(|p| p.to_str()) {
+                    touched.push(path.to_string());
+                }
             }
         }
-    }
+        touched.sort();
+        touched.dedup();
 
-    /// Count commits between an old HEAD and a new OID.
-    fn count_commits_between(repo: &Repository, old_head: Option<git2::Oid>, new_oid: git2::Oid) -> u32 {
-        let Ok(mut revwalk) = repo.revwalk() else { return 0 };
-        if revwalk.push(new_oid).is_err() {
-            return 0;
+        index.read_tree(&head_tree)?;
+        index.write()?;
+        if !touched.is_empty() {
+            let mut checkout = CheckoutBuilder::new();
+            checkout.force();
+            for path in &touched {
+                checkout.path(path);
+            }
+            self.repo.checkout_index(Some(&mut index), Some(&mut checkout))?;
         }
-        if let Some(old) = old_head {
-            let _ = revwalk.hide(old);
+        // Files the operation added are no longer in the index
+        for path in &touched {
+            if head_tree.get_path(Path::new(path)).is_err() {
+                let file = self.repo.workdir().unwrap_or(Path::new(".")).join(path);
+                if file.is_file() {
+                    std::fs::remove_file(file)?;
+                }
+            }
         }
-        revwalk.set_sorting(Sort::TIME).ok();
-        revwalk.count() as u32
+        self.repo.cleanup_state()?;
+
+        Ok(())
     }
 
-    /// Check if a commit modifies a specific file.
-    fn commit_touches_file(&self, commit: &git2::Commit, file_path: &str) -> Result<bool, AppError> {
-        let tree = commit.tree()?;
+    /// Report which multi-step operation (merge, revert, cherry-pick, rebase)
+    /// is in progress, and with which commits.
+    pub fn repo_state(&self) -> Result<RepoState, AppError> {
+        let operation = match self.repo.state() {
+            RepositoryState::Clean => RepoOperation::None,
+            RepositoryState::Merge => RepoOperation::Merge,
+            RepositoryState::Revert | RepositoryState::RevertSequence => RepoOperation::Revert,
+            RepositoryState::CherryPick | RepositoryState::CherryPickSequence => {
+                RepoOperation::CherryPick
+            }
+            RepositoryState::Bisect => RepoOperation::Bisect,
+            RepositoryState::Rebase
+            | RepositoryState::RebaseInteractive
+            | RepositoryState::RebaseMerge => RepoOperation::Rebase,
+            RepositoryState::ApplyMailbox | RepositoryState::ApplyMailboxOrRebase => {
+                RepoOperation::ApplyMailbox
+            }
+        };
 
-        let parent_tree = if commit.parent_count() > 0 {
-            Some(commit.parent(0)?.tree()?)
+        let incoming = match operation {
+            RepoOperation::Merge => self.read_state_lines("MERGE_HEAD"),
+            RepoOperation::Revert => self.read_state_lines("REVERT_HEAD"),
+            RepoOperation::CherryPick => self.read_state_lines("CHERRY_PICK_HEAD"),
+            _ => Vec::new(),
+        };
+
+        let onto = if operation == RepoOperation::Rebase {
+            self.read_state_lines("rebase-merge/onto")
+                .into_iter()
+                .chain(self.read_state_lines("rebase-apply/onto"))
+                .next()
         } else {
             None
         };
 
-        let mut diff_opts = DiffOptions::new();
-        diff_opts.pathspec(file_path);
+        let source = if operation == RepoOperation::Merge {
+            self.merge_source_name()
+        } else {
+            None
+        };
 
-        let diff = self.repo.diff_tree_to_tree(
-            parent_tree.as_ref(),
-            Some(&tree),
-            Some(&mut diff_opts),
+        Ok(RepoState {
+            operation,
+            head: self.repo.head().ok().and_then(|h| h.target()).map(|oid| oid.to_string()),
+            orig_head: self.read_state_lines("ORIG_HEAD").into_iter().next(),
+            incoming,
+            onto,
+            source,
+            conflicts: self.conflicted_paths()?,
+        })
+    }
+
+    /// Create a commit undoing `hash`, or with `path` only that file's part
+    /// of it. The inverse changes are applied with the table-aware merge, so
+    /// later edits to other cells of the same rows are kept.
+    pub fn revert(&self, hash: &str, path: Option<&str>) -> Result<ReplayResult, AppError> {
+        let commit = self.repo.revparse_single(hash)?.peel_to_commit()?;
+        let parent = commit.parent(0).map_err(|_| {
+            AppError::GitError(git2::Error::from_str("Cannot revert a commit with no parent"))
+        })?;
+        let head = self.repo.head()?.peel_to_commit()?;
+        let summary = commit.summary().unwrap_or("").to_string();
+        self.ensure_index_matches(&head, "revert")?;
+
+        let (theirs, message) = match path {
+            None => {
+                let mut opts = git2::RevertOptions::new();
+                if commit.parent_count() > 1 {
+                    opts.mainline(1);
+                }
+                self.repo.revert(&commit, Some(&mut opts))?;
+                let message = format!("Revert \"{}\"\n\nThis reverts commit {}.\n", summary, commit.id());
+                (parent.tree()?, message)
+            }
+            Some(path) => {
+                let theirs = Self::tree_with_file(&self.repo, &commit.tree()?, &parent.tree()?, path)?;
+                self.apply_file_merge(&commit.tree()?, &head.tree()?, &theirs, path)?;
+                let message = format!(
+                    "Revert \"{}\" in {}\n\nThis reverts the changes to {} from commit {}.\n",
+                    summary,
+                    path,
+                    path,
+                    commit.id()
+                );
+                (theirs, message)
+            }
+        };
+
+        let rules = self.merge_strategies()?;
+        let conflicts = self.settle_index(
+            Some(&commit.tree()?),
+            &head.tree()?,
+            &theirs,
+            self.current_branch().ok().as_deref(),
+            None,
+            &rules,
+        )?;
+        self.finish_replay(&head, &message, None, ("REVERT_HEAD", commit.id()), conflicts)
+    }
+
+    /// Apply the changes a commit introduced onto HEAD, merging CSV files at
+    /// cell level. With `files`, only those paths are picked from the commit.
+    /// Unresolvable cells are left as conflicts for `resolve_conflicts` or
+    /// `merge_abort`, as with a merge. The original author is kept.
+    pub fn cherry_pick(&self, hash: &str, files: &[String]) -> Result<ReplayResult, AppError> {
+        let commit = self.repo.revparse_single(hash)?.peel_to_commit()?;
+        let parent = commit.parent(0).map_err(|_| {
+            AppError::GitError(git2::Error::from_str("Cannot cherry-pick a commit with no parent"))
+        })?;
+        let head = self.repo.head()?.peel_to_commit()?;
+        let original = commit.message().unwrap_or("").trim_end().to_string();
+        self.ensure_index_matches(&head, "cherry-pick")?;
+
+        let (theirs, message) = if files.is_empty() {
+            let mut opts = git2::CherrypickOptions::new();
+            if commit.parent_count() > 1 {
+                opts.mainline(1);
+            }
+            self.repo.cherrypick(&commit, Some(&mut opts))?;
+            (commit.tree()?, format!("{}\n", original))
+        } else {
+            let mut theirs = parent.tree()?;
+            for path in files {
+                theirs = Self::tree_with_file(&self.repo, &theirs, &commit.tree()?, path)?;
+            }
+            for path in files {
+                self.apply_file_merge(&parent.tree()?, &head.tree()?, &theirs, path)?;
+            }
+            let message = format!(
+                "{}\n\nCherry-picked {} from commit {}.\n",
+                original,
+                files.join(", "),
+                commit.id()
+            );
+            (theirs, message)
+        };
+
+        // Name the source side after the branch when one was given
+        let their_branch = self
+            .repo
+            .find_branch(hash, BranchType::Local)
+            .ok()
+            .map(|_| hash.to_string());
+        let rules = self.merge_strategies()?;
+        let conflicts = self.settle_index(
+            Some(&parent.tree()?),
+            &head.tree()?,
+            &theirs,
+            self.current_branch().ok().as_deref(),
+            their_branch.as_deref(),
+            &rules,
         )?;
+        let author = commit.author();
+        self.finish_replay(
+            &head,
+            &message,
+            Some(&author),
+            ("CHERRY_PICK_HEAD", commit.id()),
+            conflicts,
+        )
+    }
+
+    // ── Private helpers ──────────────────────────────────────────────
+
+    /// Paths of all conflicted files in the index, whatever the conflict kind.
+    fn conflicted_paths(&self) -> Result<Vec<String>, AppError> {
+        let index = self.repo.index()?;
+        if !index.has_conflicts() {
+            return Ok(Vec::new());
+        }
+
+        let mut paths = Vec::new();
+        for conflict in index.conflicts()? {
+            let conflict = conflict?;
+            let entry = conflict
+                .our
+                .as_ref()
+                .or(conflict.their.as_ref())
+                .or(conflict.ancestor.as_ref());
+            if let Some(entry) = entry {
+                paths.push(String::from_utf8_lossy(&entry.path).to_string());
+            }
+        }
+        Ok(paths)
+    }
+
+    /// Build the model for a single index conflict, classifying its kind from
+    /// which stages are present and whether their paths agree.
+    /// Conflicted files in `index`. libgit2 records a conflicting rename as
+    /// separate entries under the old and the new path, so those are paired
+    /// up by content similarity and reported once, under the new path.
+    fn index_conflicts(&self, index: &git2::Index) -> Result<Vec<ConflictFile>, AppError> {
+        let mut files = Vec::new();
+        for conflict in index.conflicts()? {
+            files.push(self.conflict_to_model(conflict?)?);
+        }
+        self.pair_renames(&mut files, true)?;
+        self.pair_renames(&mut files, false)?;
+        Ok(files)
+    }
+
+    /// Merge the entries of files renamed by one side (theirs, or ours when
+    /// `by_theirs` is false): the old path has the ancestor but not that
+    /// side, and the new path has nothing but that side.
+    fn pair_renames(&self, files: &mut Vec<ConflictFile>, by_theirs: bool) -> Result<(), AppError> {
+        // The side that renamed, and the one that kept the old path
+        fn sides(
+            file: &mut ConflictFile,
+            by_theirs: bool,
+        ) -> (&Option<ConflictSide>, &mut Option<ConflictSide>) {
+            if by_theirs {
+                (&file.theirs, &mut file.ours)
+            } else {
+                (&file.ours, &mut file.theirs)
+            }
+        }
+
+        let mut old = Vec::new();
+        let mut new = Vec::new();
+        for file in files.iter_mut() {
+            let ancestor = file.ancestor.clone();
+            match (ancestor, sides(file, by_theirs)) {
+                (Some(ancestor), (None, _)) => old.push(ancestor),
+                (None, (Some(side), None)) => new.push(side.clone()),
+                _ => {}
+            }
+        }
+        if old.is_empty() || new.is_empty() {
+            return Ok(());
+        }
+
+        // Let libgit2's rename detection compare the candidates
+        let tree = |entries: &[ConflictSide]| -> Result<git2::Tree, AppError> {
+            let empty = self.repo.find_tree(self.repo.treebuilder(None)?.write()?)?;
+            let mut update = git2::build::TreeUpdateBuilder::new();
+            for side in entries {
+                let oid = git2::Oid::from_str(&side.oid)?;
+                update.upsert(side.path.as_str(), oid, git2::FileMode::Blob);
+            }
+            Ok(self
+                .repo
+                .find_tree(update.create_updated(&self.repo, &empty)?)?)
+        };
+        let mut diff = self
+            .repo
+            .diff_tree_to_tree(Some(&tree(&old)?), Some(&tree(&new)?), None)?;
+        diff.find_similar(Some(git2::DiffFindOptions::new().renames(true)))?;
+
+        let position = |path: Option<&Path>| {
+            let path = path?.to_string_lossy();
+            files.iter().position(|f| f.path == path)
+        };
+        let mut pairs = Vec::new();
+        for delta in diff
+            .deltas()
+            .filter(|delta| delta.status() == Delta::Renamed)
+        {
+            if let (Some(from), Some(to)) = (
+                position(delta.old_file().path()),
+                position(delta.new_file().path()),
+            ) {
+                pairs.push((from, to));
+            }
+        }
+
+        let mut paired = Vec::new();
+        for (from, to) in pairs {
+            let ancestor = files[from].ancestor.take();
+            let kept = sides(&mut files[from], by_theirs).1.take();
+            let file = &mut files[to];
+            file.ancestor = ancestor;
+            *sides(file, by_theirs).1 = kept;
+            file.kind = Self::conflict_kind(&file.ancestor, &file.ours, &file.theirs);
+            paired.push(from);
+        }
+        paired.sort_unstable();
+        for i in paired.into_iter().rev() {
+            files.remove(i);
+        }
+        Ok(())
+    }
+
+    /// Every path a conflict's entries live under: more than one for a rename.
+    fn side_paths(file: &ConflictFile) -> Vec<String> {
+        let mut paths = vec![file.path.clone()];
+        for side in [&file.ancestor, &file.ours, &file.theirs].into_iter().flatten() {
+            if !paths.contains(&side.path) {
+                paths.push(side.path.clone());
+            }
+        }
+        paths
+    }
+
+    fn conflict_to_model(&self, conflict: git2::IndexConflict) -> Result<ConflictFile, AppError> {
+        let ancestor = self.conflict_side(conflict.ancestor.as_ref())?;
+        let ours = self.conflict_side(conflict.our.as_ref())?;
+        let theirs = self.conflict_side(conflict.their.as_ref())?;
+
+        let kind = Self::conflict_kind(&ancestor, &ours, &theirs);
+
+        let path = ours
+            .as_ref()
+            .or(theirs.as_ref())
+            .or(ancestor.as_ref())
+            .map(|s| s.path.clone())
+            .unwrap_or_default();
+
+        Ok(ConflictFile {
+            path,
+            kind,
+            ancestor,
+            ours,
+            theirs,
+        })
+    }
+
+    /// Uncommitted files that a safe checkout of `target` would overwrite.
+    fn checkout_blockers(&self, target: &git2::Object) -> Result<Vec<String>, AppError> {
+        let mut blocked = Vec::new();
+        let result = {
+            let mut opts = CheckoutBuilder::new();
+            opts.safe()
+                .dry_run()
+                .notify_on(CheckoutNotificationType::CONFLICT)
+                .notify(|_, path, _, _, _| {
+                    if let Some(path) = path {
+                        blocked.push(path.to_string_lossy().to_string());
+                    }
+                    true
+                });
+            self.repo.checkout_tree(target, Some(&mut opts))
+        };
+
+        // The dry run itself fails with a conflict error when files block it
+        match result {
+            Err(e) if blocked.is_empty() => Err(e.into()),
+            _ => Ok(blocked),
+        }
+    }
+
+    /// Stash uncommitted changes, including untracked files. With `files`,
+    /// only those paths are stashed.
+    fn stash_push(&mut self, message: &str, files: &[String]) -> Result<git2::Oid, AppError> {
+        let sig = Self::default_signature(&self.repo)?.to_owned();
+        let flags = StashFlags::DEFAULT | StashFlags::INCLUDE_UNTRACKED;
+        if files.is_empty() {
+            return Ok(self.repo.stash_save(&sig, message, Some(flags))?);
+        }
+
+        // libgit2 resets the whole worktree after a path-limited stash, so
+        // keep everything and reset just the stashed paths ourselves
+        let mut opts = StashSaveOptions::new(sig.clone());
+        opts.flags(Some(flags | StashFlags::KEEP_ALL));
+        for file in files {
+            opts.pathspec(file.as_str());
+        }
+        let oid = self.repo.stash_save_ext(Some(&mut opts))?;
+
+        let head = self.repo.head()?.peel_to_commit()?;
+        let head_tree = head.tree()?;
+        self.repo.reset_default(Some(head.as_object()), files)?;
+        let mut checkout = CheckoutBuilder::new();
+        checkout.force().remove_untracked(true);
+        for file in files {
+            checkout.path(file.as_str());
+        }
+        self.repo.checkout_tree(head_tree.as_object(), Some(&mut checkout))?;
+        let workdir = self.repo.workdir().unwrap_or(Path::new("."));
+        for file in files {
+            let path = workdir.join(file);
+            if head_tree.get_path(Path::new(file)).is_err() && path.is_file() {
+                std::fs::remove_file(path)?;
+            }
+        }
+
+        // Path-limited stashes can't take a message in git2, so reword the reflog entry
+        let mut reflog = self.repo.reflog("refs/stash")?;
+        reflog.remove(0, false)?;
+        reflog.append(oid, &sig, Some(message))?;
+        reflog.write()?;
+
+        Ok(oid)
+    }
+
+    /// Apply the stash entry at `index` to the working tree, settling table
+    /// conflicts cell by cell as a merge would. With `drop`, the entry is
+    /// removed if nothing is left conflicted. Returns the conflicted paths.
+    fn stash_restore(&mut self, index: usize, drop: bool) -> Result<Vec<String>, AppError> {
+        let stash_oid = self.stash_oid(index)?;
+
+        let mut checkout = CheckoutBuilder::new();
+        checkout.allow_conflicts(true);
+        let mut opts = StashApplyOptions::new();
+        opts.checkout_options(checkout);
+        self.repo.stash_apply(index, Some(&mut opts))?;
+
+        let mut conflicts = Vec::new();
+        let textual = self.conflicted_paths()?;
+        if !textual.is_empty() {
+            let stash = self.repo.find_commit(stash_oid)?;
+            let base = stash.parent(0)?;
+            let head = self.repo.head()?.peel_to_commit()?;
+            let rules = self.merge_strategies()?;
+            conflicts = self.settle_index(
+                Some(&base.tree()?),
+                &head.tree()?,
+                &stash.tree()?,
+                self.current_branch().ok().as_deref(),
+                None,
+                &rules,
+            )?;
+
+            // Applying a stash leaves changes unstaged, so unstage what the table merge resolved
+            let resolved: Vec<&String> =
+                textual.iter().filter(|p| !conflicts.contains(p)).collect();
+            if !resolved.is_empty() {
+                self.repo.reset_default(Some(head.as_object()), resolved)?;
+            }
+        }
+
+        if drop && conflicts.is_empty() {
+            self.repo.stash_drop(index)?;
+        }
+        Ok(conflicts)
+    }
+
+    /// Commit the index as the result of a revert or cherry-pick, or leave the
+    /// conflicts, with `message` saved for `resolve_conflicts`, if any remain.
+    fn finish_replay(
+        &self,
+        head: &git2::Commit,
+        message: &str,
+        author: Option<&Signature>,
+        (state_file, source): (&str, git2::Oid),
+        conflicts: Vec<String>,
+    ) -> Result<ReplayResult, AppError> {
+        if !conflicts.is_empty() {
+            std::fs::write(self.repo.path().join("MERGE_MSG"), message)?;
+            // Single-file replays bypass libgit2, so record the operation here too
+            std::fs::write(self.repo.path().join(state_file), format!("{}\n", source))?;
+            return Ok(ReplayResult {
+                commit: None,
+                conflicts,
+            });
+        }
+
+        let mut index = self.repo.index()?;
+        let tree = self.repo.find_tree(index.write_tree()?)?;
+        let sig = Self::default_signature(&self.repo)?;
+        let author = author.unwrap_or(&sig);
+        let oid = self.repo.commit(Some("HEAD"), author, &sig, message, &tree, &[head])?;
+        self.repo.cleanup_state()?;
+
+        Ok(ReplayResult {
+            commit: Some(Self::commit_to_model(&self.repo.find_commit(oid)?)),
+            conflicts: Vec::new(),
+        })
+    }
+
+    /// Fail when the index differs from `head`. Replays commit the entire
+    /// index, so anything already staged would slip in with them.
+    fn ensure_index_matches(&self, head: &git2::Commit, action: &str) -> Result<(), AppError> {
+        let index = self.repo.index()?;
+        let diff = self.repo.diff_tree_to_index(Some(&head.tree()?), Some(&index), None)?;
+        if diff.deltas().len() > 0 {
+            return Err(AppError::GitError(git2::Error::from_str(&format!(
+                "Cannot {} with staged changes",
+                action
+            ))));
+        }
+        Ok(())
+    }
+
+    /// Three-way merge a single file into the index and working tree, leaving
+    /// everything else alone. Conflicts are recorded in the index with
+    /// markers in the file, ready for `settle_index`.
+    fn apply_file_merge(
+        &self,
+        ancestor: &git2::Tree,
+        ours: &git2::Tree,
+        theirs: &git2::Tree,
+        path: &str,
+    ) -> Result<(), AppError> {
+        let file = Path::new(path);
+        let dirty = self.repo.status_file(file).unwrap_or(git2::Status::CURRENT);
+        if !(dirty - git2::Status::IGNORED).is_empty() {
+            return Err(AppError::GitError(git2::Error::from_str(&format!(
+                "{} has uncommitted changes",
+                path
+            ))));
+        }
+
+        let merged = self.repo.merge_trees(ancestor, ours, theirs, None)?;
+        let mut index = self.repo.index()?;
+        if let Some(conflict) = merged.conflicts()?.next() {
+            let conflict = conflict?;
+            index.remove_path(file)?;
+            // The entries keep their conflict stage in their flags
+            for entry in [conflict.ancestor, conflict.our, conflict.their].into_iter().flatten() {
+                index.add(&entry)?;
+            }
+        } else {
+            match merged.get_path(file, 0) {
+                Some(entry) => index.add(&entry)?,
+                None => index.remove_path(file)?,
+            }
+        }
+        index.write()?;
+
+        let mut checkout = CheckoutBuilder::new();
+        checkout
+            .force()
+            .allow_conflicts(true)
+            .conflict_style_merge(true)
+            .remove_untracked(true)
+            .path(path);
+        self.repo.checkout_index(Some(&mut index), Some(&mut checkout))?;
+        Ok(())
+    }
+
+    /// `base` with `path` replaced by its version in `source` (or removed if
+    /// `source` lacks it).
+    fn tree_with_file<'r>(
+        repo: &'r Repository,
+        base: &git2::Tree,
+        source: &git2::Tree,
+        path: &str,
+    ) -> Result<git2::Tree<'r>, AppError> {
+        let mut update = git2::build::TreeUpdateBuilder::new();
+        match source.get_path(Path::new(path)) {
+            Ok(entry) => update.upsert(path, entry.id(), git2::FileMode::Blob),
+            Err(_) => update.remove(path),
+        };
+        let oid = update.create_updated(repo, base)?;
+        Ok(repo.find_tree(oid)?)
+    }
+
+    /// The commit a force-with-lease value names: a full hash, or an
+    /// abbreviated hash or revision known locally. Empty means the ref must
+    /// not exist on the remote yet.
+    fn lease_oid(&self, expected: &str) -> Result<git2::Oid, AppError> {
+        if expected.is_empty() {
+            return Ok(git2::Oid::zero());
+        }
+        // A full hash may name a remote commit we have never fetched
+        if expected.len() == 40 {
+            if let Ok(oid) = git2::Oid::from_str(expected) {
+                return Ok(oid);
+            }
+        }
+        Ok(self.repo.revparse_single(expected)?.peel_to_commit()?.id())
+    }
+
+    /// Fail with `StaleLease` unless `refname` on `remote` is at `expected`.
+    fn check_lease(
+        &self,
+        remote: &mut git2::Remote,
+        transfer: &Transfer,
+        refname: &str,
+        expected: &str,
+    ) -> Result<(), AppError> {
+        let actual = {
+            let connection = remote
+                .connect_auth(Direction::Push, Some(transfer.callbacks()), None)
+                .map_err(|err| transfer.map_error(err))?;
+            connection
+                .list()?
+                .iter()
+                .find(|head| head.name() == refname)
+                .map(|head| head.oid())
+                .unwrap_or_else(git2::Oid::zero)
+        };
+        let expected_oid = self.lease_oid(expected)?;
+        if actual != expected_oid {
+            return Err(AppError::StaleLease {
+                branch: refname.trim_start_matches("refs/heads/").to_string(),
+                expected: expected_oid.to_string(),
+                actual: actual.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Push one ref through `remote`, as a forced update when `force`.
+    /// `label` names the remote in errors.
+    fn push_ref(
+        &self,
+        remote: &mut git2::Remote,
+        transfer: &Transfer,
+        label: &str,
+        refname: &str,
+        force: bool,
+    ) -> Result<Vec<PushRefStatus>, AppError> {
+        let mut refspec = format!("{}:{}", refname, refname);
+        if force {
+            refspec.insert(0, '+');
+        }
+
+        let mut push_opts = PushOptions::new();
+        push_opts.remote_callbacks(transfer.callbacks());
+        remote
+            .push(&[&refspec], Some(&mut push_opts))
+            .map_err(|err| match transfer.map_error(err) {
+                // Some transports refuse before reporting per-ref status
+                AppError::PushRejected { .. } => AppError::PushRejected {
+                    remote: label.to_string(),
+                    refs: vec![refname.to_string()],
+                },
+                err => err,
+            })?;
+
+        let refs = transfer.pushed();
+        let rejected: Vec<String> = refs
+            .iter()
+            .filter(|status| !status.ok)
+            .map(|status| status.name.clone())
+            .collect();
+        if !rejected.is_empty() {
+            return Err(AppError::PushRejected {
+                remote: label.to_string(),
+                refs: rejected,
+            });
+        }
+        Ok(refs)
+    }
+
+    /// All push URLs configured for a remote (`remote.<name>.pushurl`).
+    fn push_urls(&self, name: &str) -> Result<Vec<String>, AppError> {
+        let config = self.repo.config()?.snapshot()?;
+        let mut urls = Vec::new();
+        let mut entries = config.multivar(&format!("remote.{}.pushurl", name), None)?;
+        while let Some(entry) = entries.next() {
+            if let Some(url) = entry?.value() {
+                urls.push(url.to_string());
+            }
+        }
+        Ok(urls)
+    }
+
+    /// Remote of the current branch's upstream, falling back to `origin`.
+    fn default_remote(&self) -> String {
+        self.repo
+            .head()
+            .ok()
+            .and_then(|head| head.name().map(str::to_string))
+            .and_then(|name| self.repo.branch_upstream_remote(&name).ok())
+            .and_then(|buf| buf.as_str().map(str::to_string))
+            .unwrap_or_else(|| "origin".to_string())
+    }
+
+    /// Name, tip and upstream tracking counts for a branch.
+    fn branch_info(&self, branch: &git2::Branch, name: &str) -> Result<BranchInfo, AppError> {
+        let tip = branch.get().target();
+        let upstream = branch.upstream().ok();
+
+        let (ahead, behind) = match (tip, upstream.as_ref().and_then(|u| u.get().target())) {
+            (Some(local), Some(remote)) => self.repo.graph_ahead_behind(local, remote)?,
+            _ => (0, 0),
+        };
+        let upstream = match upstream {
+            Some(upstream) => upstream.name()?.map(str::to_string),
+            None => None,
+        };
+
+        Ok(BranchInfo {
+            name: name.to_string(),
+            hash: tip.map(|oid| oid.to_string()).unwrap_or_default(),
+            upstream,
+            ahead,
+            behind,
+        })
+    }
+
+    fn tag_info(&self, name: &str) -> Result<TagInfo, AppError> {
+        let object = self.repo.revparse_single(&format!("refs/tags/{}", name))?;
+        let commit = object.peel_to_commit()?;
+        let (message, tagger) = match object.as_tag() {
+            Some(tag) => (
+                tag.message().map(str::to_string),
+                tag.tagger().and_then(|t| t.name().map(str::to_string)),
+            ),
+            None => (None, None),
+        };
+        Ok(TagInfo {
+            name: name.to_string(),
+            annotated: object.as_tag().is_some(),
+            message,
+            tagger,
+            commit: Self::commit_to_model(&commit),
+        })
+    }
+
+    /// Report a stash application in the same shape as a merge.
+    fn stash_merge_result(conflicts: Vec<String>) -> MergeResult {
+        MergeResult {
+            success: conflicts.is_empty(),
+            conflicts: if conflicts.is_empty() {
+                None
+            } else {
+                Some(conflicts)
+            },
+        }
+    }
+
+    /// Commit ID of the stash entry at `index` (0 is the most recent).
+    fn stash_oid(&self, index: usize) -> Result<git2::Oid, AppError> {
+        let reflog = self.repo.reflog("refs/stash")?;
+        let entry = reflog.get(index).ok_or_else(|| {
+            AppError::GitError(git2::Error::from_str(&format!("No stash entry {}", index)))
+        })?;
+        Ok(entry.id_new())
+    }
+
+    /// Classify a conflict from which sides exist and whether their paths agree.
+    fn conflict_kind(
+        ancestor: &Option<ConflictSide>,
+        ours: &Option<ConflictSide>,
+        theirs: &Option<ConflictSide>,
+    ) -> ConflictKind {
+        let paths: Vec<&str> = [ancestor, ours, theirs]
+            .iter()
+            .filter_map(|side| side.as_ref().map(|s| s.path.as_str()))
+            .collect();
+        if paths.windows(2).any(|pair| pair[0] != pair[1]) {
+            return ConflictKind::Renamed;
+        }
+
+        match (ancestor, ours, theirs) {
+            (None, _, _) => ConflictKind::BothAdded,
+            (Some(_), None, _) => ConflictKind::DeletedByUs,
+            (Some(_), _, None) => ConflictKind::DeletedByThem,
+            (Some(_), Some(_), Some(_)) => ConflictKind::BothModified,
+        }
+    }
+
+    /// Load the blob behind one index stage of a conflict.
+    fn conflict_side(&self, entry: Option<&git2::IndexEntry>) -> Result<Option<ConflictSide>, AppError> {
+        let Some(entry) = entry else { return Ok(None) };
+        let path = String::from_utf8_lossy(&entry.path).to_string();
+        self.blob_side(&path, Some(entry.id))
+    }
+
+    /// Build one side of a conflict from a blob, if it exists.
+    fn blob_side(&self, path: &str, oid: Option<git2::Oid>) -> Result<Option<ConflictSide>, AppError> {
+        let Some(oid) = oid else { return Ok(None) };
+        let blob = self.repo.find_blob(oid)?;
+        Ok(Some(ConflictSide {
+            path: path.to_string(),
+            oid: oid.to_string(),
+            content: std::str::from_utf8(blob.content()).ok().map(|s| s.to_string()),
+        }))
+    }
+
+    /// After libgit2 has merged `their_commit` into the index and working
+    /// tree, settle files using the configured merge strategies and the
+    /// cell-level table merge. Returns the paths that still conflict.
+    fn settle_merge(
+        &self,
+        their_commit: &git2::Commit,
+        their_branch: Option<&str>,
+    ) -> Result<Vec<String>, AppError> {
+        let head = self.repo.head()?.peel_to_commit()?;
+        let ancestor = self.merge_base_tree(head.id(), their_commit.id())?;
+        let rules = self.merge_strategies()?;
+        self.settle_index(
+            ancestor.as_ref(),
+            &head.tree()?,
+            &their_commit.tree()?,
+            self.current_branch().ok().as_deref(),
+            their_branch,
+            &rules,
+        )
+    }
+
+    /// Replay the remaining rebase steps, settling each with the table-aware
+    /// merge. Stops at the first step that leaves cell conflicts.
+    fn run_rebase(&self, rebase: &mut git2::Rebase) -> Result<RebaseResult, AppError> {
+        let sig = Self::default_signature(&self.repo)?;
+        let rules = self.merge_strategies()?;
+        // Upstream is "ours" while replaying; the local branch is "theirs"
+        let our_branch = self
+            .read_state_lines("rebase-merge/onto_name")
+            .into_iter()
+            .next()
+            .map(|name| self.owner_branch_name(&name));
+        let their_branch = rebase
+            .orig_head_name()
+            .map(|name| name.trim_start_matches("refs/heads/").to_string());
+
+        while let Some(operation) = rebase.next() {
+            let commit = self.repo.find_commit(operation?.id())?;
+            let ancestor = match commit.parent_count() {
+                0 => None,
+                _ => Some(commit.parent(0)?.tree()?),
+            };
+            let ours = self.repo.head()?.peel_to_tree()?;
+            let conflicts = self.settle_index(
+                ancestor.as_ref(),
+                &ours,
+                &commit.tree()?,
+                our_branch.as_deref(),
+                their_branch.as_deref(),
+                &rules,
+            )?;
+            if !conflicts.is_empty() {
+                return Ok(Self::rebase_stopped(rebase, &commit, conflicts));
+            }
+            Self::rebase_commit(rebase, &sig)?;
+        }
+
+        rebase.finish(Some(&sig))?;
+        Ok(RebaseResult {
+            finished: true,
+            applied: rebase.len(),
+            total: rebase.len(),
+            stopped_at: None,
+            conflicts: Vec::new(),
+        })
+    }
+
+    /// Commit the current rebase step, skipping it if it became empty.
+    fn rebase_commit(rebase: &mut git2::Rebase, sig: &Signature) -> Result<(), AppError> {
+        match rebase.commit(None, sig, None) {
+            Err(err) if err.code() != ErrorCode::Applied => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn rebase_stopped(
+        rebase: &mut git2::Rebase,
+        commit: &git2::Commit,
+        conflicts: Vec<String>,
+    ) -> RebaseResult {
+        RebaseResult {
+            finished: false,
+            applied: rebase.operation_current().unwrap_or(0),
+            total: rebase.len(),
+            stopped_at: Some(Self::commit_to_model(commit)),
+            conflicts,
+        }
+    }
+
+    fn open_rebase(&self) -> Result<git2::Rebase<'_>, AppError> {
+        self.repo.open_rebase(None).map_err(|err| match err.code() {
+            ErrorCode::NotFound => AppError::NoRebaseInProgress,
+            _ => err.into(),
+        })
+    }
+
+    /// Stage resolved files, which also clears their conflict entries. A file
+    /// resolved by deletion is removed from the index instead. Resolving a
+    /// renamed file settles the entries under its old path too.
+    fn stage_resolved(&self, files: &[String]) -> Result<git2::Index, AppError> {
+        let mut index = self.repo.index()?;
+        let workdir = self.repo.workdir().unwrap_or(Path::new("."));
+        let mut paths: Vec<String> = files.to_vec();
+        for conflict in self.index_conflicts(&index)? {
+            if conflict.kind == ConflictKind::Renamed && files.contains(&conflict.path) {
+                paths.extend(Self::side_paths(&conflict));
+            }
+        }
+        paths.sort();
+        paths.dedup();
+        for file in &paths {
+            let path = Path::new(file);
+            if workdir.join(path).exists() {
+                index.add_path(path)?;
+            } else {
+                index.remove_path(path)?;
+            }
+        }
+        index.write()?;
+        Ok(index)
+    }
+
+    /// Settle every file both sides changed, given the three trees the merge
+    /// was computed from and the strategy rules to honour. Resolved files are
+    /// written to the working tree and staged. Files with cell conflicts stay
+    /// conflicted: under a rule they get a provisional table (ours wins the
+    /// conflicting cells), otherwise they keep libgit2's conflict markers.
+    fn settle_index(
+        &self,
+        ancestor: Option<&git2::Tree>,
+        ours: &git2::Tree,
+        theirs: &git2::Tree,
+        our_branch: Option<&str>,
+        their_branch: Option<&str>,
+        rules: &[MergeStrategyRule],
+    ) -> Result<Vec<String>, AppError> {
+        let workdir = self.repo.workdir().unwrap_or(Path::new(".")).to_path_buf();
+        let mut index = self.repo.index()?;
+
+        let mut files = self.index_conflicts(&index)?;
+        let conflicted: HashSet<String> = files.iter().flat_map(Self::side_paths).collect();
+        files.extend(self.strategy_candidates(ancestor, ours, theirs, rules, &conflicted)?);
+
+        let mut checkout = CheckoutBuilder::new();
+        let mut taken_blobs = false;
+        for file in &files {
+            let rule = merge_strategy::rule_for(rules, &file.path);
+            let Some(resolution) =
+                merge_strategy::resolve(file, rule, our_branch, their_branch)
+            else {
+                continue;
+            };
+            if rule.is_none() && !resolution.conflicts.is_empty() {
+                continue;
+            }
+
+            let path = Path::new(&file.path);
+            let full_path = workdir.join(path);
+            match resolution.merged {
+                Merged::Text(content) => {
+                    if let Some(parent) = full_path.parent() {
+                        std::fs::create_dir_all(parent)?;
+                    }
+                    std::fs::write(&full_path, content)?;
+                    if resolution.conflicts.is_empty() {
+                        index.add_path(path)?;
+                    }
+                }
+                Merged::Blob(oid) => {
+                    // Stage the blob itself, then check it out like any other file
+                    let mode = [ours, theirs]
+                        .iter()
+                        .filter_map(|tree| tree.get_path(path).ok())
+                        .find(|entry| entry.id() == oid)
+                        .map(|entry| entry.filemode() as u32)
+                        .unwrap_or(0o100644);
+                    index.remove_path(path)?;
+                    index.add(&Self::index_entry(&file.path, oid, mode))?;
+                    checkout.path(&file.path);
+                    taken_blobs = true;
+                }
+                Merged::Deleted => {
+                    if full_path.exists() {
+                        std::fs::remove_file(&full_path)?;
+                    }
+                    index.remove_path(path)?;
+                }
+            }
+        }
+
+        index.write()?;
+        if taken_blobs {
+            checkout.force().allow_conflicts(true);
+            self.repo.checkout_index(Some(&mut index), Some(&mut checkout))?;
+        }
+        self.conflicted_paths()
+    }
+
+    /// A stage-0 index entry for a blob already in the object database.
+    fn index_entry(path: &str, oid: git2::Oid, mode: u32) -> git2::IndexEntry {
+        git2::IndexEntry {
+            ctime: git2::IndexTime::new(0, 0),
+            mtime: git2::IndexTime::new(0, 0),
+            dev: 0,
+            ino: 0,
+            mode,
+            uid: 0,
+            gid: 0,
+            file_size: 0,
+            id: oid,
+            flags: 0,
+            flags_extended: 0,
+            path: path.as_bytes().to_vec(),
+        }
+    }
+
+    /// The branch name column-ownership rules use for `name`: remote-tracking
+    /// names such as `origin/pricing` become `pricing`.
+    fn owner_branch_name(&self, name: &str) -> String {
+        let name = name
+            .trim_start_matches("refs/heads/")
+            .trim_start_matches("refs/remotes/");
+        if self.repo.find_branch(name, BranchType::Local).is_ok() {
+            return name.to_string();
+        }
+        if let Ok(remotes) = self.repo.remotes() {
+            for remote in remotes.iter().flatten() {
+                let branch = name.strip_prefix(remote).and_then(|rest| rest.strip_prefix('/'));
+                if let Some(branch) = branch {
+                    return branch.to_string();
+                }
+            }
+        }
+        name.to_string()
+    }
+
+    /// Files that merged cleanly as text but have a non-default strategy rule
+    /// and were changed on both sides, so the rule must still be applied.
+    fn strategy_candidates(
+        &self,
+        ancestor: Option<&git2::Tree>,
+        ours: &git2::Tree,
+        theirs: &git2::Tree,
+        rules: &[MergeStrategyRule],
+        skip: &HashSet<String>,
+    ) -> Result<Vec<ConflictFile>, AppError> {
+        if rules.iter().all(|r| r.strategy == MergeStrategy::Cells) {
+            return Ok(Vec::new());
+        }
+
+        let ancestor_files = match ancestor {
+            Some(tree) => Self::tree_blobs(tree)?,
+            None => HashMap::new(),
+        };
+        let our_files = Self::tree_blobs(ours)?;
+        let their_files = Self::tree_blobs(theirs)?;
+
+        let paths: BTreeSet<&String> = our_files.keys().chain(their_files.keys()).collect();
+        let mut candidates = Vec::new();
+        for path in paths {
+            let has_rule = merge_strategy::rule_for(rules, path)
+                .map(|r| r.strategy != MergeStrategy::Cells)
+                .unwrap_or(false);
+            if skip.contains(path) || !has_rule {
+                continue;
+            }
+
+            let a = ancestor_files.get(path).copied();
+            let o = our_files.get(path).copied();
+            let t = their_files.get(path).copied();
+            // Only one side changed: the textual merge already took that side
+            if o == t || a == o || a == t {
+                continue;
+            }
+
+            let ancestor = self.blob_side(path, a)?;
+            let ours = self.blob_side(path, o)?;
+            let theirs = self.blob_side(path, t)?;
+            candidates.push(ConflictFile {
+                path: path.clone(),
+                kind: Self::conflict_kind(&ancestor, &ours, &theirs),
+                ancestor,
+                ours,
+                theirs,
+            });
+        }
+
+        Ok(candidates)
+    }
+
+    /// Tree of the merge base of two commits, if they share history.
+    fn merge_base_tree(
+        &self,
+        one: git2::Oid,
+        two: git2::Oid,
+    ) -> Result<Option<git2::Tree<'_>>, AppError> {
+        match self.repo.merge_base(one, two) {
+            Ok(base) => Ok(Some(self.repo.find_commit(base)?.tree()?)),
+            Err(e) if e.code() == git2::ErrorCode::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Remove every config key of the strategy rule for `pattern`.
+    fn clear_strategy_keys(config: &mut git2::Config, pattern: &str) -> Result<(), AppError> {
+        let prefix = format!("{}.{}", merge_strategy::CONFIG_SECTION, pattern);
+        for variable in ["strategy", "key"] {
+            match config.remove(&format!("{}.{}", prefix, variable)) {
+                Err(e) if e.code() != git2::ErrorCode::NotFound => return Err(e.into()),
+                _ => {}
+            }
+        }
+        match config.remove_multivar(&format!("{}.owner", prefix), ".*") {
+            Err(e) if e.code() != git2::ErrorCode::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Per-file changes between two trees, with table diffs for CSV/TSV files.
+    /// Renames are detected so a moved sheet shows up as one entry.
+    fn diff_trees(
+        &self,
+        old_tree: Option<&git2::Tree>,
+        new_tree: Option<&git2::Tree>,
+    ) -> Result<Vec<FileDiff>, AppError> {
+        let mut diff = self.repo.diff_tree_to_tree(old_tree, new_tree, None)?;
+        diff.find_similar(None)?;
+
+        let mut files = Vec::new();
+        for delta in diff.deltas() {
+            let old_path = delta.old_file().path().map(|p| p.to_string_lossy().to_string());
+            let new_path = delta.new_file().path().map(|p| p.to_string_lossy().to_string());
+            let path = new_path.or(old_path.clone()).unwrap_or_default();
+            let renamed_from = if delta.status() == Delta::Renamed {
+                old_path
+            } else {
+                None
+            };
+
+            let old = self.blob_text(delta.old_file().id())?;
+            let new = self.blob_text(delta.new_file().id())?;
+            files.push(Self::file_diff(
+                &path,
+                renamed_from.as_deref(),
+                old.as_deref(),
+                new.as_deref(),
+            ));
+        }
+
+        Ok(files)
+    }
+
+    /// Describe the change to one file. `None` content means the file does
+    /// not exist on that side.
+    fn file_diff(
+        path: &str,
+        old_path: Option<&str>,
+        old: Option<&str>,
+        new: Option<&str>,
+    ) -> FileDiff {
+        let change = match (old, new) {
+            (None, _) => FileChangeKind::Added,
+            (_, None) => FileChangeKind::Deleted,
+            _ if old_path.is_some() => FileChangeKind::Renamed,
+            _ => FileChangeKind::Modified,
+        };
+
+        let table = if table_diff::is_table_path(path) {
+            let parse = |content: Option<&str>| match content {
+                Some(content) => Table::parse(content, path).ok(),
+                None => Some(Table::empty(b',')),
+            };
+            match (parse(old), parse(new)) {
+                (Some(old), Some(new)) => Some(table_diff::diff_tables(&old, &new, &[])),
+                _ => None,
+            }
+        } else {
+            None
+        };
+
+        FileDiff {
+            path: path.to_string(),
+            old_path: old_path.map(|p| p.to_string()),
+            change,
+            table,
+        }
+    }
+
+    /// Map of every blob path in a tree to its object ID.
+    fn tree_blobs(tree: &git2::Tree) -> Result<HashMap<String, git2::Oid>, AppError> {
+        let mut files = HashMap::new();
+        tree.walk(TreeWalkMode::PreOrder, |root, entry| {
+            if entry.kind() == Some(ObjectType::Blob) {
+                let name = entry.name().unwrap_or("");
+                files.insert(format!("{}{}", root, name), entry.id());
+            }
+            TreeWalkResult::Ok
+        })?;
+        Ok(files)
+    }
+
+    /// Text content of a blob, or `None` for the zero OID (file absent).
+    fn blob_text(&self, oid: git2::Oid) -> Result<Option<String>, AppError> {
+        if oid.is_zero() {
+            return Ok(None);
+        }
+        let blob = self.repo.find_blob(oid)?;
+        Ok(Some(String::from_utf8_lossy(blob.content()).to_string()))
+    }
+
+    /// Read the non-empty lines of a state file under `.git` (e.g. the object
+    /// IDs in MERGE_HEAD). Missing files yield an empty list.
+    fn read_state_lines(&self, name: &str) -> Vec<String> {
+        std::fs::read_to_string(self.repo.path().join(name))
+            .map(|content| {
+                content
+                    .lines()
+                    .map(|line| line.trim())
+                    .filter(|line| !line.is_empty())
+                    .map(|line| line.to_string())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Name of the branch being merged, parsed from the first line of MERGE_MSG
+    /// (e.g. `Merge branch 'feature'` or `Merge remote-tracking branch 'origin/main'`).
+    fn merge_source_name(&self) -> Option<String> {
+        let message = self.repo.message().ok()?;
+        let first_line = message.lines().next()?;
+        if !first_line.starts_with("Merge ") {
+            return None;
+        }
+        let start = first_line.find('\'')? + 1;
+        let len = first_line[start..].find('\'')?;
+        Some(first_line[start..start + len].to_string())
+    }
+
+    /// Get the current branch name.
+    fn current_branch(&self) -> Result<String, AppError> {
+        let head = self.repo.head()?;
+        let name = head
+            .shorthand()
+            .unwrap_or("HEAD")
+            .to_string();
+        Ok(name)
+    }
+
+    /// Create a default signature using "Ledgit" as the author.
+    fn default_signature(repo: &Repository) -> Result<Signature<'_>, AppError> {
+        // Try to get signature from git config first
+        match repo.signature() {
+            Ok(sig) => Ok(sig),
+            Err(_) => {
+                let sig = Signature::now("Ledgit", "ledgit@local")?;
+                Ok(sig)
+            }
+        }
+    }
+
+    /// Count commits between an old HEAD and a new OID.
+    fn count_commits_between(repo: &Repository, old_head: Option<git2::Oid>, new_oid: git2::Oid) -> u32 {
+        let Ok(mut revwalk) = repo.revwalk() else { return 0 };
+        if revwalk.push(new_oid).is_err() {
+            return 0;
+        }
+        if let Some(old) = old_head {
+            let _ = revwalk.hide(old);
+        }
+        revwalk.set_sorting(Sort::TIME).ok();
+        revwalk.count() as u32
+    }
+
+    /// Check if a commit modifies a specific file.
+    fn commit_touches_file(&self, commit: &git2::Commit, file_path: &str) -> Result<bool, AppError> {
+        let tree = commit.tree()?;
+
+        let parent_tree = if commit.parent_count() > 0 {
+            Some(commit.parent(0)?.tree()?)
+        } else {
+            None
+        };
+
+        let mut diff_opts = DiffOptions::new();
+        diff_opts.pathspec(file_path);
+
+        let diff = self.repo.diff_tree_to_tree(
+            parent_tree.as_ref(),
+            Some(&tree),
+            Some(&mut diff_opts),
+        )?;
+
+        Ok(diff.deltas().count() > 0)
+    }
+
+    /// Build a map from commit OID to ref names (branch names + HEAD).
+    fn build_ref_map(&self) -> HashMap<git2::Oid, Vec<String>> {
+        let mut map: HashMap<git2::Oid, Vec<String>> = HashMap::new();
+
+        // Add branch names
+        if let Ok(branches) = self.repo.branches(Some(BranchType::Local)) {
+            for branch_result in branches {
+                if let Ok((branch, _)) = branch_result {
+                    let name = branch.name().ok().flatten().map(|n| n.to_string());
+                    if let Some(name) = name {
+                        if let Ok(reference) = branch.into_reference().resolve() {
+                            if let Some(oid) = reference.target() {
+                                map.entry(oid).or_default().push(name);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        // Add tag names, peeling annotated tags to their commit
+        if let Ok(names) = self.repo.tag_names(None) {
+            for name in names.iter().flatten() {
+                let target = self
+                    .repo
+                    .revparse_single(&format!("refs/tags/{}", name))
+                    .and_then(|object| object.peel_to_commit());
+                if let Ok(commit) = target {
+                    map.entry(commit.id()).or_default().push(name.to_string());
+                }
+            }
+        }
+
+        // Add remote-tracking branches, skipping symbolic refs such as origin/HEAD
+        if let Ok(branches) = self.repo.branches(Some(BranchType::Remote)) {
+            for (branch, _) in branches.flatten() {
+                if branch.get().symbolic_target().is_some() {
+                    continue;
+                }
+                if let (Ok(Some(name)), Some(oid)) = (branch.name(), branch.get().target()) {
+                    map.entry(oid).or_default().push(name.to_string());
+                }
+            }
+        }
+
+        // Add HEAD
+        if let Ok(head) = self.repo.head() {
+            if let Some(oid) = head.target() {
+                map.entry(oid).or_default().push("HEAD".to_string());
+            }
+        }
+
+        map
+    }
+
+    fn identity(signature: &Signature) -> Identity {
+        let when = signature.when();
+        let offset = chrono::FixedOffset::east_opt(when.offset_minutes() * 60)
+            .unwrap_or_else(|| chrono::FixedOffset::east_opt(0).unwrap());
+        Identity {
+            name: signature.name().unwrap_or("Unknown").to_string(),
+            email: signature.email().unwrap_or("").to_string(),
+            timestamp: chrono::DateTime::from_timestamp(when.seconds(), 0)
+                .map(|dt| dt.with_timezone(&offset).to_rfc3339())
+                .unwrap_or_default(),
+            offset_minutes: when.offset_minutes(),
+        }
+    }
+
+    /// Format seconds since the epoch as an RFC 3339 UTC timestamp.
+    fn format_timestamp(seconds: i64) -> String {
+        chrono::DateTime::from_timestamp(seconds, 0)
+            .map(|dt| dt.to_rfc3339())
+            .unwrap_or_default()
+    }
+
+    /// Full name of the ref whose reflog to read: branch names are looked up
+    /// under `refs/heads/`.
+    fn reflog_name(&self, reference: &str) -> String {
+        if reference == "HEAD" || reference.starts_with("refs/") {
+            return reference.to_string();
+        }
+        let branch = format!("refs/heads/{}", reference);
+        if self.repo.find_reference(&branch).is_ok() {
+            branch
+        } else {
+            reference.to_string()
+        }
+    }
+
+    fn reflog_entry_to_model(&self, index: usize, entry: &git2::ReflogEntry) -> ReflogEntry {
+        let committer = entry.committer();
+        ReflogEntry {
+            index,
+            old_hash: entry.id_old().to_string(),
+            new_hash: entry.id_new().to_string(),
+            message: entry.message().unwrap_or("").to_string(),
+            committer: committer.name().unwrap_or("Unknown").to_string(),
+            timestamp: Self::format_timestamp(committer.when().seconds()),
+            // Commits the entry points at may have been pruned since
+            summary: self.reflog_summary(entry).unwrap_or_default(),
+        }
+    }
+
+    /// Describe what a reflog entry changed, file by file.
+    fn reflog_summary(&self, entry: &git2::ReflogEntry) -> Result<String, AppError> {
+        let tree = |oid: git2::Oid| -> Result<Option<git2::Tree>, AppError> {
+            if oid.is_zero() {
+                return Ok(None);
+            }
+            Ok(Some(self.repo.find_commit(oid)?.tree()?))
+        };
+        let (old, new) = (tree(entry.id_old())?, tree(entry.id_new())?);
+        let files = self.diff_trees(old.as_ref(), new.as_ref())?;
+        let summary = if files.is_empty() {
+            "No changes detected".to_string()
+        } else {
+            files
+                .iter()
+                .map(|file| match &file.table {
+                    Some(table) => format!("{}: {}", file.path, table.summary),
+                    None => {
+                        let change = format!("{:?}", file.change).to_lowercase();
+                        format!("{}: {}", file.path, change)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("; ")
+        };
+        Ok(summary)
+    }
+
+    /// Convert a git2::Commit to our model Commit (no refs).
+    fn commit_to_model(commit: &git2::Commit) -> Commit {
+        Self::commit_to_model_with_refs(commit, Vec::new())
+    }
+
+    /// Convert a git2::Commit to our model Commit with refs.
+    fn commit_to_model_with_refs(commit: &git2::Commit, refs: Vec<String>) -> Commit {
+        let hash = commit.id().to_string();
+        let short_hash = hash[..7.min(hash.len())].to_string();
+        let message = commit.message().unwrap_or("").to_string();
+        let author = commit.author();
+        let committer = commit.committer();
+        let timestamp = Self::format_timestamp(commit.time().seconds());
+
+        Commit {
+            hash,
+            short_hash,
+            message,
+            author: author.name().unwrap_or("Unknown").to_string(),
+            author_email: author.email().unwrap_or("").to_string(),
+            author_timestamp: Self::format_timestamp(author.when().seconds()),
+            committer: committer.name().unwrap_or("Unknown").to_string(),
+            committer_email: committer.email().unwrap_or("").to_string(),
+            timestamp,
+            parents: commit.parent_ids().map(|oid| oid.to_string()).collect(),
+            refs,
+            graph: None,
+        }
+    }
+}
+
+/// Split a comma-separated config value into trimmed, non-empty items.
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|item| item.trim())
+        .filter(|item| !item.is_empty())
+        .map(|item| item.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::git::TransferStage;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_init_and_open() {
+        let dir = TempDir::new().unwrap();
+        let service = GitService::init(dir.path()).unwrap();
+        let status = service.status().unwrap();
+        assert!(status.clean);
+
+        let _reopened = GitService::open(dir.path()).unwrap();
+    }
+
+    #[test]
+    fn test_commit_and_log() {
+        let dir = TempDir::new().unwrap();
+        let service = GitService::init(dir.path()).unwrap();
+
+        // Create a test file
+        std::fs::write(dir.path().join("test.csv"), "a,b\n1,2\n").unwrap();
+
+        let commit = service
+            .commit("Add test file", &["test.csv".to_string()])
+            .unwrap();
+        assert_eq!(commit.message, "Add test file");
+
+        let log = service.log(None, 10, 0, &LogOptions::default()).unwrap();
+        assert!(log.len() >= 2); // initial + our commit
+    }
+
+    #[test]
+    fn test_checkout_updates_working_directory() {
+        let dir = TempDir::new().unwrap();
+        let mut service = GitService::init(dir.path()).unwrap();
+
+        // Create data.csv on main and commit it
+        std::fs::write(dir.path().join("data.csv"), "a,b\n1,2\n").unwrap();
+        service
+            .commit("Add data.csv on main", &["data.csv".to_string()])
+            .unwrap();
+
+        // Create a new branch "feature" and switch to it
+        service.create_branch("feature", None).unwrap();
+        service.checkout("feature", false).unwrap();
+
+        // Modify the file on feature branch and commit
+        std::fs::write(dir.path().join("data.csv"), "a,b\n3,4\n").unwrap();
+        service
+            .commit("Update data.csv on feature", &["data.csv".to_string()])
+            .unwrap();
+
+        // Switch back to main
+        service.checkout("main", false).unwrap();
+        let content = std::fs::read_to_string(dir.path().join("data.csv")).unwrap();
+        assert_eq!(content, "a,b\n1,2\n", "checkout main should restore original file content");
+
+        // Switch to feature again
+        service.checkout("feature", false).unwrap();
+        let content = std::fs::read_to_string(dir.path().join("data.csv")).unwrap();
+        assert_eq!(content, "a,b\n3,4\n", "checkout feature should restore modified file content");
+    }
+
+    #[test]
+    fn test_conflicts_reports_stages_and_kind() {
+        let dir = TempDir::new().unwrap();
+        let mut service = GitService::init(dir.path()).unwrap();
+
+        std::fs::write(dir.path().join("data.csv"), "id,v\n1,a\n").unwrap();
+        std::fs::write(dir.path().join("gone.csv"), "id,v\n1,a\n").unwrap();
+        service
+            .commit("Base", &["data.csv".to_string(), "gone.csv".to_string()])
+            .unwrap();
+
+        service.create_branch("feature", None).unwrap();
+        service.checkout("feature", false).unwrap();
+        std::fs::write(dir.path().join("data.csv"), "id,v\n1,theirs\n").unwrap();
+        std::fs::write(dir.path().join("gone.csv"), "id,v\n1,edited\n").unwrap();
+        service
+            .commit("Feature edits", &["data.csv".to_string(), "gone.csv".to_string()])
+            .unwrap();
+
+        service.checkout("main", false).unwrap();
+        std::fs::write(dir.path().join("data.csv"), "id,v\n1,ours\n").unwrap();
+        std::fs::remove_file(dir.path().join("gone.csv")).unwrap();
+        service
+            .commit("Main edits", &["data.csv".to_string(), "gone.csv".to_string()])
+            .unwrap();
+
+        let result = service.merge("feature").unwrap();
+        assert!(!result.success);
+        let mut conflicts = result.conflicts.unwrap();
+        conflicts.sort();
+        assert_eq!(conflicts, vec!["data.csv".to_string(), "gone.csv".to_string()]);
+
+        let list = service.conflicts().unwrap();
+        assert_eq!(list.source.as_deref(), Some("feature"));
+        assert!(list.merge_head.is_some());
+
+        let data = list.files.iter().find(|f| f.path == "data.csv").unwrap();
+        assert_eq!(data.kind, ConflictKind::BothModified);
+        assert_eq!(data.ancestor.as_ref().unwrap().content.as_deref(), Some("id,v\n1,a\n"));
+        assert_eq!(data.ours.as_ref().unwrap().content.as_deref(), Some("id,v\n1,ours\n"));
+        assert_eq!(data.theirs.as_ref().unwrap().content.as_deref(), Some("id,v\n1,theirs\n"));
+
+        let gone = list.files.iter().find(|f| f.path == "gone.csv").unwrap();
+        assert_eq!(gone.kind, ConflictKind::DeletedByUs);
+        assert!(gone.ours.is_none());
+    }
+
+    #[test]
+    fn test_conflicts_pair_rename_with_modify() {
+        let body: String = (2..20).map(|i| format!("{},row{}\n", i, i)).collect();
+        let table = |first: &str| format!("id,v\n1,{}\n{}", first, body);
+
+        // Once with the rename on their side, once on ours
+        for ours_renames in [false, true] {
+            let dir = TempDir::new().unwrap();
+            let mut service = GitService::init(dir.path()).unwrap();
+            let write =
+                |name: &str, content: &str| std::fs::write(dir.path().join(name), content).unwrap();
+            let both = ["data.csv".to_string(), "moved.csv".to_string()];
+
+            write("data.csv", &table("a"));
+            service.commit("Base", &both[..1]).unwrap();
+            let main = service.current_branch().unwrap();
+            service.create_branch("feature", None).unwrap();
+            let (renamer, editor) = if ours_renames {
+                (main.as_str(), "feature")
+            } else {
+                ("feature", main.as_str())
+            };
+
+            service.checkout(renamer, false).unwrap();
+            std::fs::remove_file(dir.path().join("data.csv")).unwrap();
+            write("moved.csv", &table("renamed"));
+            service.commit("Rename", &both).unwrap();
+            service.checkout(editor, false).unwrap();
+            write("data.csv", &table("edited"));
+            service.commit("Edit", &both[..1]).unwrap();
+            service.checkout(&main, false).unwrap();
+
+            let preview = service.merge_preview("feature").unwrap();
+            assert_eq!(preview.conflicts.len(), 1);
+            assert_eq!(preview.conflicts[0].kind, ConflictKind::Renamed);
+
+            let result = service.merge("feature").unwrap();
+            assert!(!result.success);
+            let list = service.conflicts().unwrap();
+            assert_eq!(list.files.len(), 1);
+            let file = &list.files[0];
+            assert_eq!(file.path, "moved.csv");
+            assert_eq!(file.kind, ConflictKind::Renamed);
+            assert_eq!(file.ancestor.as_ref().unwrap().path, "data.csv");
+            let (renamed, edited) = if ours_renames {
+                (&file.ours, &file.theirs)
+            } else {
+                (&file.theirs, &file.ours)
+            };
+            assert_eq!(renamed.as_ref().unwrap().path, "moved.csv");
+            assert_eq!(edited.as_ref().unwrap().content.as_deref(), Some(table("edited").as_str()));
+
+            // Resolving the new path settles the old one as well
+            write("moved.csv", &table("both"));
+            let commit = service.resolve_conflicts(&["moved.csv".to_string()]).unwrap();
+            let tree = service.repo.revparse_single(&commit.hash).unwrap().peel_to_tree().unwrap();
+            assert!(tree.get_path(Path::new("moved.csv")).is_ok());
+            assert!(tree.get_path(Path::new("data.csv")).is_err());
+        }
+    }
+
+    #[test]
+    fn test_merge_abort_restores_pre_merge_state() {
+        let dir = TempDir::new().unwrap();
+        let mut service = GitService::init(dir.path()).unwrap();
+
+        std::fs::write(dir.path().join("data.csv"), "id,v\n1,a\n").unwrap();
+        std::fs::write(dir.path().join("notes.csv"), "id\n1\n").unwrap();
+        service
+            .commit("Base", &["data.csv".to_string(), "notes.csv".to_string()])
+            .unwrap();
+
+        service.create_branch("feature", None).unwrap();
+        service.checkout("feature", false).unwrap();
+        std::fs::write(dir.path().join("data.csv"), "id,v\n1,theirs\n").unwrap();
+        std::fs::write(dir.path().join("extra.csv"), "id\n1\n").unwrap();
+        service
+            .commit("Theirs", &["data.csv".to_string(), "extra.csv".to_string()])
+            .unwrap();
+
+        service.checkout("main", false).unwrap();
+        std::fs::write(dir.path().join("data.csv"), "id,v\n1,ours\n").unwrap();
+        let ours = service.commit("Ours", &["data.csv".to_string()]).unwrap();
+        // An edit the merge doesn't touch survives the abort
+        std::fs::write(dir.path().join("notes.csv"), "id\n1\n2\n").unwrap();
+
+        assert!(matches!(service.merge_abort(), Err(AppError::NoMergeInProgress)));
+
+        let result = service.merge("feature").unwrap();
+        assert!(!result.success);
+
+        let state = service.repo_state().unwrap();
+        assert_eq!(state.operation, RepoOperation::Merge);
+        assert_eq!(state.incoming.len(), 1);
+        assert_eq!(state.source.as_deref(), Some("feature"));
+        assert_eq!(state.conflicts, vec!["data.csv".to_string()]);
+        // With no rule the conflicting cell is left marked, not settled as ours
+        let content = std::fs::read_to_string(dir.path().join("data.csv")).unwrap();
+        assert!(content.contains("<<<<<<<"), "{}", content);
+
+        service.merge_abort().unwrap();
+
+        let state = service.repo_state().unwrap();
+        assert_eq!(state.operation, RepoOperation::None);
+        assert!(state.conflicts.is_empty());
+        assert_eq!(state.head.as_deref(), Some(ours.hash.as_str()));
+        let content = std::fs::read_to_string(dir.path().join("data.csv")).unwrap();
+        assert_eq!(content, "id,v\n1,ours\n");
+        assert!(!dir.path().join("extra.csv").exists());
+        let content = std::fs::read_to_string(dir.path().join("notes.csv")).unwrap();
+        assert_eq!(content, "id\n1\n2\n");
+        let status = service.status().unwrap();
+        assert_eq!(status.modified, vec!["notes.csv".to_string()]);
+        assert!(status.staged.is_empty() && status.untracked.is_empty());
+    }
+
+    #[test]
+    fn test_merge_preview_leaves_worktree_untouched() {
+        let dir = TempDir::new().unwrap();
+        let mut service = GitService::init(dir.path()).unwrap();
+
+        std::fs::write(dir.path().join("data.csv"), "id,a,b\n1,x,y\n2,x,y\n").unwrap();
+        std::fs::write(dir.path().join("lookup.csv"), "id,name\n1,a\n2,b\n3,c\n").unwrap();
+        service
+            .commit("Base", &["data.csv".to_string(), "lookup.csv".to_string()])
+            .unwrap();
+
+        service.create_branch("feature", None).unwrap();
+        service.checkout("feature", false).unwrap();
+        std::fs::write(dir.path().join("data.csv"), "id,a,b\n1,x,THEIRS\n2,x,theirs\n").unwrap();
+        std::fs::write(dir.path().join("extra.csv"), "id\n1\n").unwrap();
+        service
+            .commit("Theirs", &["data.csv".to_string(), "extra.csv".to_string()])
+            .unwrap();
+
+        // Fast-forward preview from main before it diverges
+        service.checkout("main", false).unwrap();
+        let preview = service.merge_preview("feature").unwrap();
+        assert!(preview.fast_forward);
+        assert_eq!(preview.files.len(), 2);
+
+        std::fs::write(dir.path().join("data.csv"), "id,a,b\n1,OURS,y\n2,x,ours\n").unwrap();
+        service.commit("Ours", &["data.csv".to_string()]).unwrap();
+
+        // A rename on their side shows up as one renamed file
+        service.checkout("feature", false).unwrap();
+        std::fs::rename(dir.path().join("lookup.csv"), dir.path().join("codes.csv")).unwrap();
+        service
+            .commit("Rename", &["lookup.csv".to_string(), "codes.csv".to_string()])
+            .unwrap();
+        service.checkout("main", false).unwrap();
+
+        let preview = service.merge_preview("feature").unwrap();
+        let codes = preview.files.iter().find(|f| f.path == "codes.csv").unwrap();
+        assert_eq!(codes.change, FileChangeKind::Renamed);
+        assert_eq!(codes.old_path.as_deref(), Some("lookup.csv"));
+        assert!(!preview.files.iter().any(|f| f.path == "lookup.csv"));
+        assert!(!preview.fast_forward);
+        assert!(!preview.up_to_date);
+
+        let extra = preview.files.iter().find(|f| f.path == "extra.csv").unwrap();
+        assert_eq!(extra.change, FileChangeKind::Added);
+        assert_eq!(extra.table.as_ref().unwrap().added_rows, vec!["1"]);
+
+        // Row 1 merges cleanly at cell level; row 2 column b conflicts
+        assert_eq!(preview.conflicts.len(), 1);
+        let conflict = &preview.conflicts[0];
+        assert_eq!(conflict.path, "data.csv");
+        assert_eq!(conflict.cells.len(), 1);
+        assert_eq!(conflict.cells[0].row_key, "2");
+        assert_eq!(conflict.cells[0].column, "b");
+
+        let data = preview.files.iter().find(|f| f.path == "data.csv").unwrap();
+        let cells = &data.table.as_ref().unwrap().modified_cells;
+        assert_eq!(cells.len(), 1);
+        assert_eq!(cells[0].new_value, "THEIRS");
+
+        assert!(service.status().unwrap().clean);
+        assert_eq!(service.repo_state().unwrap().operation, RepoOperation::None);
+    }
+
+    #[test]
+    fn test_merge_strategy_config_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let service = GitService::init(dir.path()).unwrap();
+
+        let rule = MergeStrategyRule {
+            pattern: "ref/*.csv".to_string(),
+            strategy: MergeStrategy::ColumnOwnership,
+            key_columns: vec!["id".to_string(), "region".to_string()],
+            column_owners: vec![
+                ColumnOwner {
+                    branch: "pricing".to_string(),
+                    columns: vec!["price".to_string(), "currency".to_string()],
+                },
+                ColumnOwner {
+                    branch: "ops".to_string(),
+                    columns: vec!["stock".to_string()],
+                },
+            ],
+        };
+        service.set_merge_strategy(&rule).unwrap();
+        assert_eq!(service.merge_strategies().unwrap(), vec![rule.clone()]);
+
+        // Replacing a rule drops its old owners
+        let replacement = MergeStrategyRule {
+            strategy: MergeStrategy::Ours,
+            column_owners: Vec::new(),
+            ..rule
+        };
+        service.set_merge_strategy(&replacement).unwrap();
+        assert_eq!(service.merge_strategies().unwrap(), vec![replacement.clone()]);
+
+        // Editing a rule keeps its precedence over later ones
+        let fallback = MergeStrategyRule {
+            pattern: "*.csv".to_string(),
+            strategy: MergeStrategy::Union,
+            key_columns: Vec::new(),
+            column_owners: Vec::new(),
+        };
+        service.set_merge_strategy(&fallback).unwrap();
+        let edited = MergeStrategyRule {
+            strategy: MergeStrategy::ColumnOwnership,
+            key_columns: Vec::new(),
+            column_owners: rule.column_owners.clone(),
+            ..replacement
+        };
+        service.set_merge_strategy(&edited).unwrap();
+        assert_eq!(service.merge_strategies().unwrap(), vec![edited, fallback]);
+        service.remove_merge_strategy("*.csv").unwrap();
+
+        service.remove_merge_strategy("ref/*.csv").unwrap();
+        assert!(service.merge_strategies().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_merge_applies_union_strategy_and_cell_merge() {
+        let dir = TempDir::new().unwrap();
+        let mut service = GitService::init(dir.path()).unwrap();
+        service
+            .set_merge_strategy(&MergeStrategyRule {
+                pattern: "log.csv".to_string(),
+                strategy: MergeStrategy::Union,
+                key_columns: Vec::new(),
+                column_owners: Vec::new(),
+            })
+            .unwrap();
+
+        let files = vec!["log.csv".to_string(), "data.csv".to_string()];
+        std::fs::write(dir.path().join("log.csv"), "id,event\n1,start\n").unwrap();
+        std::fs::write(dir.path().join("data.csv"), "id,a,b\n1,x,y\n").unwrap();
+        service.commit("Base", &files).unwrap();
+
+        service.create_branch("feature", None).unwrap();
+        service.checkout("feature", false).unwrap();
+        std::fs::write(dir.path().join("log.csv"), "id,event\n1,start\n3,theirs\n").unwrap();
+        std::fs::write(dir.path().join("data.csv"), "id,a,b\n1,x,THEIRS\n").unwrap();
+        service.commit("Theirs", &files).unwrap();
+
+        service.checkout("main", false).unwrap();
+        std::fs::write(dir.path().join("log.csv"), "id,event\n1,start\n2,ours\n").unwrap();
+        std::fs::write(dir.path().join("data.csv"), "id,a,b\n1,OURS,y\n").unwrap();
+        service.commit("Ours", &files).unwrap();
+
+        let result = service.merge("feature").unwrap();
+        assert!(result.success, "conflicts: {:?}", result.conflicts);
+
+        let log = std::fs::read_to_string(dir.path().join("log.csv")).unwrap();
+        assert_eq!(log, "id,event\n1,start\n2,ours\n3,theirs\n");
+        let data = std::fs::read_to_string(dir.path().join("data.csv")).unwrap();
+        assert_eq!(data, "id,a,b\n1,OURS,THEIRS\n");
+        assert!(service.status().unwrap().clean);
+        assert_eq!(service.repo_state().unwrap().operation, RepoOperation::None);
+    }
+
+    #[test]
+    fn test_ours_strategy_keeps_non_utf8_files() {
+        let dir = TempDir::new().unwrap();
+        let mut service = GitService::init(dir.path()).unwrap();
+        service
+            .set_merge_strategy(&MergeStrategyRule {
+                pattern: "*.dat".to_string(),
+                strategy: MergeStrategy::Ours,
+                key_columns: Vec::new(),
+                column_owners: Vec::new(),
+            })
+            .unwrap();
+
+        // Latin-1 bytes: a.dat conflicts as text, b.dat merges cleanly
+        let files = vec!["a.dat".to_string(), "b.dat".to_string()];
+        let write = |name: &str, content: &[u8]| std::fs::write(dir.path().join(name), content).unwrap();
+        write("a.dat", b"1;Jos\xe9\n2;x\n3;y\n");
+        write("b.dat", b"1;Jos\xe9\n2;x\n3;y\n");
+        service.commit("Base", &files).unwrap();
+
+        service.create_branch("feature", None).unwrap();
+        service.checkout("feature", false).unwrap();
+        write("a.dat", b"1;Ren\xe9e\n2;x\n3;y\n");
+        write("b.dat", b"1;Jos\xe9\n2;x\n3;theirs\n");
+        service.commit("Theirs", &files).unwrap();
+
+        service.checkout("main", false).unwrap();
+        write("a.dat", b"1;Andr\xe9\n2;x\n3;y\n");
+        write("b.dat", b"1;Andr\xe9\n2;x\n3;y\n");
+        service.commit("Ours", &files).unwrap();
+
+        let result = service.merge("feature").unwrap();
+        assert!(result.success, "conflicts: {:?}", result.conflicts);
+        for name in ["a.dat", "b.dat"] {
+            let content = std::fs::read(dir.path().join(name)).unwrap();
+            assert_eq!(content, b"1;Andr\xe9\n2;x\n3;y\n", "{}", name);
+        }
+        let head = service.repo.head().unwrap().peel_to_tree().unwrap();
+        assert!(head.get_path(Path::new("a.dat")).is_ok());
+        assert!(service.status().unwrap().clean);
+    }
+
+    #[test]
+    fn test_checkout_refuses_to_overwrite_uncommitted_changes() {
+        let dir = TempDir::new().unwrap();
+        let mut service = GitService::init(dir.path()).unwrap();
+
+        std::fs::write(dir.path().join("data.csv"), "id,a,b\n1,x,y\n").unwrap();
+        service.commit("Base", &["data.csv".to_string()]).unwrap();
+
+        service.create_branch("feature", None).unwrap();
+        service.checkout("feature", false).unwrap();
+        std::fs::write(dir.path().join("data.csv"), "id,a,b\n1,x,FEATURE\n").unwrap();
+        service.commit("Feature", &["data.csv".to_string()]).unwrap();
+        service.checkout("main", false).unwrap();
+
+        // Uncommitted edit to a file that differs on the target branch
+        std::fs::write(dir.path().join("data.csv"), "id,a,b\n1,LOCAL,y\n").unwrap();
+
+        let result = service.checkout("feature", false).unwrap();
+        assert!(!result.switched);
+        assert_eq!(result.blocked, vec!["data.csv".to_string()]);
+        assert_eq!(service.status().unwrap().branch, "main");
+        let content = std::fs::read_to_string(dir.path().join("data.csv")).unwrap();
+        assert_eq!(content, "id,a,b\n1,LOCAL,y\n");
+
+        // Auto-stash carries the edit over, merging it cell by cell
+        let result = service.checkout("feature", true).unwrap();
+        assert!(result.switched);
+        assert!(result.stashed);
+        assert!(result.conflicts.is_empty(), "conflicts: {:?}", result.conflicts);
+        assert_eq!(service.status().unwrap().branch, "feature");
+        let content = std::fs::read_to_string(dir.path().join("data.csv")).unwrap();
+        assert_eq!(content, "id,a,b\n1,LOCAL,FEATURE\n");
+
+        let status = service.status().unwrap();
+        assert_eq!(status.modified, vec!["data.csv".to_string()]);
+        assert!(status.staged.is_empty());
+        assert!(service.repo.reflog("refs/stash").map(|r| r.is_empty()).unwrap_or(true));
+    }
+
+    #[test]
+    fn test_checkout_keeps_auto_stash_that_conflicts() {
+        let dir = TempDir::new().unwrap();
+        let mut service = GitService::init(dir.path()).unwrap();
+
+        std::fs::write(dir.path().join("data.csv"), "id,a\n1,x\n").unwrap();
+        service.commit("Base", &["data.csv".to_string()]).unwrap();
+        service.create_branch("feature", None).unwrap();
+        service.checkout("feature", false).unwrap();
+        std::fs::write(dir.path().join("data.csv"), "id,a\n1,FEATURE\n").unwrap();
+        service.commit("Feature", &["data.csv".to_string()]).unwrap();
+        service.checkout("main", false).unwrap();
+
+        // Same cell edited locally: the switch stands and the stash stays listed
+        std::fs::write(dir.path().join("data.csv"), "id,a\n1,LOCAL\n").unwrap();
+        let result = service.checkout("feature", true).unwrap();
+        assert!(result.switched);
+        assert!(result.stashed);
+        assert!(result.stash_kept);
+        assert!(result.stash_error.is_none());
+        assert_eq!(result.conflicts, vec!["data.csv".to_string()]);
+        assert_eq!(service.status().unwrap().branch, "feature");
+        assert_eq!(service.stash_list().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_stash_save_list_and_pop() {
+        let dir = TempDir::new().unwrap();
+        let mut service = GitService::init(dir.path()).unwrap();
+
+        std::fs::write(dir.path().join("a.csv"), "id,v\n1,x\n").unwrap();
+        std::fs::write(dir.path().join("b.csv"), "id,v\n1,x\n").unwrap();
+        service
+            .commit("Base", &["a.csv".to_string(), "b.csv".to_string()])
+            .unwrap();
+
+        std::fs::write(dir.path().join("a.csv"), "id,v\n1,edited\n2,new\n").unwrap();
+        std::fs::write(dir.path().join("b.csv"), "id,v\n1,kept\n").unwrap();
+
+        // Only a.csv is stashed; b.csv stays modified
+        let entry = service.stash_save("WIP on a", &["a.csv".to_string()]).unwrap();
+        assert_eq!(entry.index, 0);
+        assert_eq!(entry.message, "WIP on a");
+        assert_eq!(entry.files.len(), 1);
+        let table = entry.files[0].table.as_ref().unwrap();
+        assert_eq!(table.added_rows, vec!["2"]);
+        assert_eq!(table.modified_cells.len(), 1);
+
+        let status = service.status().unwrap();
+        assert_eq!(status.modified, vec!["b.csv".to_string()]);
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.csv")).unwrap(),
+            "id,v\n1,x\n"
+        );
+
+        let result = service.stash_pop(0).unwrap();
+        assert!(result.success);
+        assert!(service.stash_list().unwrap().is_empty());
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.csv")).unwrap(),
+            "id,v\n1,edited\n2,new\n"
+        );
+    }
+
+    #[test]
+    fn test_stash_apply_reports_cell_conflicts() {
+        let dir = TempDir::new().unwrap();
+        let mut service = GitService::init(dir.path()).unwrap();
+
+        std::fs::write(dir.path().join("data.csv"), "id,v\n1,x\n").unwrap();
+        service.commit("Base", &["data.csv".to_string()]).unwrap();
+
+        std::fs::write(dir.path().join("data.csv"), "id,v\n1,stashed\n").unwrap();
+        service.stash_save("Edit", &[]).unwrap();
+
+        std::fs::write(dir.path().join("data.csv"), "id,v\n1,committed\n").unwrap();
+        service.commit("Other edit", &["data.csv".to_string()]).unwrap();
+
+        let result = service.stash_apply(0).unwrap();
+        assert!(!result.success);
+        assert_eq!(result.conflicts, Some(vec!["data.csv".to_string()]));
+        assert_eq!(service.stash_list().unwrap().len(), 1);
+
+        service.stash_drop(0).unwrap();
+        assert!(service.stash_list().unwrap().is_empty());
+
+        // A configured strategy settles the file like it would in a merge
+        {
+            let head = service.repo.head().unwrap().peel_to_commit().unwrap();
+            service.repo.reset(head.as_object(), ResetType::Hard, None).unwrap();
+        }
+        std::fs::write(dir.path().join("data.csv"), "id,v\n1,stashed\n").unwrap();
+        service.stash_save("Edit", &[]).unwrap();
+        service
+            .set_merge_strategy(&MergeStrategyRule {
+                pattern: "*.csv".to_string(),
+                strategy: MergeStrategy::Theirs,
+                key_columns: Vec::new(),
+                column_owners: Vec::new(),
+            })
+            .unwrap();
+        let result = service.stash_pop(0).unwrap();
+        assert!(result.success);
+        let content = std::fs::read_to_string(dir.path().join("data.csv")).unwrap();
+        assert_eq!(content, "id,v\n1,stashed\n");
+        assert!(service.stash_list().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_branch_management() {
+        let dir = TempDir::new().unwrap();
+        let service = GitService::init(dir.path()).unwrap();
+
+        std::fs::write(dir.path().join("data.csv"), "id,v\n1,x\n").unwrap();
+        service.commit("Base", &["data.csv".to_string()]).unwrap();
+        let base = service.repo.head().unwrap().peel_to_commit().unwrap();
+
+        // A branch one commit ahead of main is unmerged
+        service.create_branch("feature", None).unwrap();
+        let sig = Signature::now("Test", "test@test.com").unwrap();
+        let tree = base.tree().unwrap();
+        service
+            .repo
+            .commit(Some("refs/heads/feature"), &sig, &sig, "Ahead", &tree, &[&base])
+            .unwrap();
+
+        assert!(matches!(
+            service.delete_branch("feature", false),
+            Err(AppError::UnmergedBranch(_))
+        ));
+        assert!(service.delete_branch("main", true).is_err());
+
+        // Use a local "remote" by pointing a tracking ref at main
+        service.repo.remote("origin", "file:///nowhere").unwrap();
+        service
+            .repo
+            .reference("refs/remotes/origin/feature", base.id(), false, "test")
+            .unwrap();
+        service.set_upstream("feature", Some("origin/feature")).unwrap();
+
+        let list = service.branches().unwrap();
+        let feature = list.local.iter().find(|b| b.name == "feature").unwrap();
+        assert_eq!(feature.upstream.as_deref(), Some("origin/feature"));
+        assert_eq!((feature.ahead, feature.behind), (1, 0));
+        assert_eq!(list.remote.len(), 1);
+        assert_eq!(list.remote[0].name, "origin/feature");
+
+        service.rename_branch("feature", "renamed").unwrap();
+        let list = service.branches().unwrap();
+        let renamed = list.local.iter().find(|b| b.name == "renamed").unwrap();
+        assert_eq!(renamed.upstream.as_deref(), Some("origin/feature"));
+
+        service.set_upstream("renamed", None).unwrap();
+        service.delete_branch("renamed", true).unwrap();
+
+        assert_eq!(service.track_branch("origin/feature", None).unwrap(), "feature");
+        let list = service.branches().unwrap();
+        let tracked = list.local.iter().find(|b| b.name == "feature").unwrap();
+        assert_eq!(tracked.upstream.as_deref(), Some("origin/feature"));
+        // Tracking branch at the upstream tip counts as merged
+        service.delete_branch("feature", false).unwrap();
+    }
+
+    #[test]
+    fn test_clone_from_local_remote() {
+        let origin_dir = TempDir::new().unwrap();
+        let origin = GitService::init(origin_dir.path()).unwrap();
+        std::fs::write(origin_dir.path().join("data.csv"), "id,v\n1,x\n").unwrap();
+        origin.commit("Add data", &["data.csv".to_string()]).unwrap();
+        origin.create_branch("feature", None).unwrap();
+
+        let url = format!("file://{}", origin_dir.path().display());
+        let dest = TempDir::new().unwrap();
+        let cancel = AtomicBool::new(false);
+        let mut updates = Vec::new();
+        let clone = GitService::clone(
+            &url,
+            &dest.path().join("copy"),
+            Some("feature"),
+            Some(1),
+            &RemoteAuth::default(),
+            &cancel,
+            &mut |p| updates.push(p.stage),
+        )
+        .unwrap();
+
+        assert_eq!(clone.current_branch().unwrap(), "feature");
+        assert!(dest.path().join("copy/data.csv").exists());
+        assert!(updates.contains(&TransferStage::CheckingOut));
+        assert_eq!(clone.remotes().unwrap()[0].url, url);
+
+        // A plain path works too
+        let path = origin_dir.path().to_string_lossy().to_string();
+        let auth = RemoteAuth::default();
+        GitService::clone(&path, &dest.path().join("by-path"), None, None, &auth, &cancel, &mut |_| {})
+            .unwrap();
+        assert!(dest.path().join("by-path/data.csv").exists());
+
+        cancel.store(true, std::sync::atomic::Ordering::Relaxed);
+        let result = GitService::clone(&url, &dest.path().join("cancelled"), None, None, &auth, &cancel, &mut |_| {});
+        assert!(matches!(result, Err(AppError::Cancelled)));
+    }
+
+    #[test]
+    fn test_push_reports_rejected_and_unreachable() {
+        let origin_dir = TempDir::new().unwrap();
+        let origin = GitService::init(origin_dir.path()).unwrap();
+        // Work on a branch the origin does not have checked out, so the only
+        // reason for it to refuse the push below is that it is not a fast-forward
+        origin.create_branch("shared", None).unwrap();
+
+        let url = format!("file://{}", origin_dir.path().display());
+        let dest = TempDir::new().unwrap();
+        let auth = RemoteAuth::default();
+        let cancel = AtomicBool::new(false);
+        let clone = GitService::clone(&url, dest.path(), Some("shared"), None, &auth, &cancel, &mut |_| {})
+            .unwrap();
+
+        // Diverge the remote so the push is not a fast-forward
+        std::fs::write(origin_dir.path().join("a.csv"), "id\n1\n").unwrap();
+        let head = origin.repo.head().unwrap().peel_to_commit().unwrap();
+        let sig = Signature::now("Test", "test@test.com").unwrap();
+        origin
+            .repo
+            .commit(Some("refs/heads/shared"), &sig, &sig, "Remote", &head.tree().unwrap(), &[&head])
+            .unwrap();
+        std::fs::write(dest.path().join("b.csv"), "id\n2\n").unwrap();
+        clone.commit("Local", &["b.csv".to_string()]).unwrap();
+
+        assert!(matches!(
+            clone.push("origin", "shared", None, &auth, &cancel, &mut |_| {}),
+            Err(AppError::PushRejected { refs, .. }) if refs == vec!["refs/heads/shared".to_string()]
+        ));
+
+        clone.add_remote("offline", "https://127.0.0.1:1/data.git").unwrap();
+        assert!(matches!(
+            clone.push("offline", "shared", None, &auth, &cancel, &mut |_| {}),
+            Err(AppError::HostUnreachable(_))
+        ));
+    }
+
+    #[test]
+    fn test_push_force_with_lease() {
+        let seed_dir = TempDir::new().unwrap();
+        GitService::init(seed_dir.path()).unwrap();
+        let bare_dir = TempDir::new().unwrap();
+        RepoBuilder::new()
+            .bare(true)
+            .clone(&seed_dir.path().to_string_lossy(), bare_dir.path())
+            .unwrap();
+        let url = format!("file://{}", bare_dir.path().display());
+
+        let auth = RemoteAuth::default();
+        let cancel = AtomicBool::new(false);
+        let (ours_dir, theirs_dir) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        let ours = GitService::clone(&url, ours_dir.path(), None, None, &auth, &cancel, &mut |_| {})
+            .unwrap();
+        let theirs = GitService::clone(&url, theirs_dir.path(), None, None, &auth, &cancel, &mut |_| {})
+            .unwrap();
+        let base = ours.repo.head().unwrap().target().unwrap().to_string();
+
+        std::fs::write(theirs_dir.path().join("a.csv"), "id\n1\n").unwrap();
+        theirs.commit("Theirs", &["a.csv".to_string()]).unwrap();
+        let result = theirs
+            .push("origin", "main", None, &auth, &cancel, &mut |_| {})
+            .unwrap();
+        assert_eq!(result.refs.len(), 1);
+        assert!(result.refs[0].ok);
+        let pushed = theirs.repo.head().unwrap().target().unwrap().to_string();
+
+        std::fs::write(ours_dir.path().join("b.csv"), "id\n2\n").unwrap();
+        ours.commit("Ours", &["b.csv".to_string()]).unwrap();
+
+        // Our view of the remote is stale, so the lease fails. An abbreviated
+        // hash is resolved, not zero-padded
+        assert!(matches!(
+            ours.push("origin", "main", Some(&base[..7]), &auth, &cancel, &mut |_| {}),
+            Err(AppError::StaleLease { expected, actual, .. }) if expected == base && actual == pushed
+        ));
+
+        // Having seen their commit, we can knowingly overwrite it
+        let result = ours
+            .push("origin", "main", Some(&pushed), &auth, &cancel, &mut |_| {})
+            .unwrap();
+        assert!(result.refs[0].ok);
+        let remote_head = Repository::open_bare(bare_dir.path())
+            .unwrap()
+            .refname_to_id("refs/heads/main")
+            .unwrap();
+        assert_eq!(remote_head, ours.repo.head().unwrap().target().unwrap());
+    }
+
+    #[test]
+    fn test_fetch_updates_tracking_refs_only() {
+        let origin_dir = TempDir::new().unwrap();
+        let origin = GitService::init(origin_dir.path()).unwrap();
+        let url = format!("file://{}", origin_dir.path().display());
+        let dest = TempDir::new().unwrap();
+        let auth = RemoteAuth::default();
+        let cancel = AtomicBool::new(false);
+        let clone = GitService::clone(&url, dest.path(), None, None, &auth, &cancel, &mut |_| {})
+            .unwrap();
+
+        std::fs::write(origin_dir.path().join("data.csv"), "id\n1\n").unwrap();
+        origin.commit("Remote change", &["data.csv".to_string()]).unwrap();
+        std::fs::write(dest.path().join("local.csv"), "id\n2\n").unwrap();
+        clone.commit("Local change", &["local.csv".to_string()]).unwrap();
+
+        let result = clone.fetch(None, &auth, &cancel, &mut |_| {}).unwrap();
+        assert_eq!(result.remote, "origin");
+        let update = result
+            .updated
+            .iter()
+            .find(|u| u.name == "refs/remotes/origin/main")
+            .unwrap();
+        assert!(update.old.is_some() && update.new.is_some());
+
+        // Nothing merged into the working tree
+        assert!(!dest.path().join("data.csv").exists());
+        let info = clone.repo_info().unwrap();
+        assert_eq!(info.upstream.as_deref(), Some("origin/main"));
+        assert_eq!((info.ahead, info.behind), (1, 1));
+    }
 
-        Ok(diff.deltas().count() > 0)
+    /// Origin and a clone sharing `data.csv`, then diverged: origin edits
+    /// `origin_row`, the clone edits `local_row`.
+    fn diverged_clone(origin_row: &str, local_row: &str) -> (TempDir, TempDir, GitService, git2::Oid) {
+        let origin_dir = TempDir::new().unwrap();
+        let origin = GitService::init(origin_dir.path()).unwrap();
+        std::fs::write(origin_dir.path().join("data.csv"), "id,v\n1,a\n2,b\n").unwrap();
+        origin.commit("Base", &["data.csv".to_string()]).unwrap();
+
+        let url = format!("file://{}", origin_dir.path().display());
+        let dest = TempDir::new().unwrap();
+        let cancel = AtomicBool::new(false);
+        let clone = GitService::clone(&url, dest.path(), None, None, &RemoteAuth::default(), &cancel, &mut |_| {})
+            .unwrap();
+
+        std::fs::write(origin_dir.path().join("data.csv"), origin_row).unwrap();
+        origin.commit("Remote edit", &["data.csv".to_string()]).unwrap();
+        let remote_head = origin.repo.head().unwrap().target().unwrap();
+
+        std::fs::write(dest.path().join("data.csv"), local_row).unwrap();
+        clone.commit("Local edit", &["data.csv".to_string()]).unwrap();
+
+        (origin_dir, dest, clone, remote_head)
     }
 
-    /// Build a map from commit OID to ref names (branch names + HEAD).
-    fn build_ref_map(&self) -> HashMap<git2::Oid, Vec<String>> {
-        let mut map: HashMap<git2::Oid, Vec<String>> = HashMap::new();
+    #[test]
+    fn test_pull_rebase_replays_with_cell_merge() {
+        // Adjacent lines conflict as text but touch different cells
+        let (_origin_dir, dest, clone, remote_head) =
+            diverged_clone("id,v\n1,remote\n2,b\n", "id,v\n1,a\n2,local\n");
+        let auth = RemoteAuth::default();
 
-        // Add branch names
-        if let Ok(branches) = self.repo.branches(Some(BranchType::Local)) {
-            for branch_result in branches {
-                if let Ok((branch, _)) = branch_result {
-                    let name = branch.name().ok().flatten().map(|n| n.to_string());
-                    if let Some(name) = name {
-                        if let Ok(reference) = branch.into_reference().resolve() {
-                            if let Some(oid) = reference.target() {
-                                map.entry(oid).or_default().push(name);
-                            }
-                        }
-                    }
-                }
-            }
+        // The fetch can be cancelled like any other transfer
+        let cancel = AtomicBool::new(true);
+        let result = clone.pull("origin", "main", true, &auth, &cancel, &mut |_| {});
+        assert!(matches!(result, Err(AppError::Cancelled)));
+        cancel.store(false, std::sync::atomic::Ordering::Relaxed);
+
+        let mut updates = Vec::new();
+        let result = clone
+            .pull("origin", "main", true, &auth, &cancel, &mut |p| updates.push(p.stage))
+            .unwrap();
+        assert!(!updates.is_empty());
+        let rebase = result.rebase.unwrap();
+        assert!(rebase.finished);
+        assert_eq!((rebase.applied, rebase.total), (1, 1));
+        assert_eq!(result.new_commits, 1);
+
+        let head = clone.repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.parent_count(), 1);
+        assert_eq!(head.parent_id(0).unwrap(), remote_head);
+        assert_eq!(head.message(), Some("Local edit"));
+        assert_eq!(clone.current_branch().unwrap(), "main");
+        assert_eq!(
+            std::fs::read_to_string(dest.path().join("data.csv")).unwrap(),
+            "id,v\n1,remote\n2,local\n"
+        );
+    }
+
+    #[test]
+    fn test_pull_rebase_stops_on_conflict_then_continue_or_abort() {
+        let (_origin_dir, dest, clone, remote_head) =
+            diverged_clone("id,v\n1,remote\n2,b\n", "id,v\n1,local\n2,b\n");
+        let (auth, cancel) = (RemoteAuth::default(), AtomicBool::new(false));
+        let local_head = clone.repo.head().unwrap().target().unwrap();
+
+        let result = clone
+            .pull("origin", "main", true, &auth, &cancel, &mut |_| {})
+            .unwrap();
+        assert!(!result.updated);
+        let rebase = result.rebase.unwrap();
+        assert!(!rebase.finished);
+        assert_eq!(rebase.conflicts, vec!["data.csv".to_string()]);
+        assert_eq!(rebase.stopped_at.unwrap().message, "Local edit");
+        assert_eq!(clone.repo_state().unwrap().operation, RepoOperation::Rebase);
+
+        clone.rebase_abort().unwrap();
+        assert_eq!(clone.repo.head().unwrap().target().unwrap(), local_head);
+        assert_eq!(clone.repo_state().unwrap().operation, RepoOperation::None);
+        assert!(matches!(clone.rebase_continue(&[]), Err(AppError::NoRebaseInProgress)));
+
+        // Run it again and resolve this time
+        clone.pull("origin", "main", true, &auth, &cancel, &mut |_| {}).unwrap();
+        // Still conflicted until the file is staged
+        assert!(!clone.rebase_continue(&[]).unwrap().finished);
+
+        std::fs::write(dest.path().join("data.csv"), "id,v\n1,both\n2,b\n").unwrap();
+        let result = clone.rebase_continue(&["data.csv".to_string()]).unwrap();
+        assert!(result.finished);
+        let head = clone.repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.parent_id(0).unwrap(), remote_head);
+        assert_eq!(clone.current_branch().unwrap(), "main");
+        assert!(clone.status().unwrap().clean);
+    }
+
+    #[test]
+    fn test_pull_honours_column_owner_named_by_branch() {
+        let (_origin_dir, dest, mut clone, _) =
+            diverged_clone("id,v\n1,remote\n2,b\n", "id,v\n1,local\n2,b\n");
+        let (auth, cancel) = (RemoteAuth::default(), AtomicBool::new(false));
+        clone.create_branch("work", None).unwrap();
+        clone.checkout("work", false).unwrap();
+        clone
+            .set_merge_strategy(&MergeStrategyRule {
+                pattern: "data.csv".to_string(),
+                strategy: MergeStrategy::ColumnOwnership,
+                key_columns: Vec::new(),
+                column_owners: vec![ColumnOwner {
+                    branch: "main".to_string(),
+                    columns: vec!["v".to_string()],
+                }],
+            })
+            .unwrap();
+
+        // origin/main is "main" to the rule, so its value wins the cell
+        let result = clone
+            .pull("origin", "main", false, &auth, &cancel, &mut |_| {})
+            .unwrap();
+        assert!(result.conflicts.is_none(), "conflicts: {:?}", result.conflicts);
+        assert_eq!(
+            std::fs::read_to_string(dest.path().join("data.csv")).unwrap(),
+            "id,v\n1,remote\n2,b\n"
+        );
+    }
+
+    #[test]
+    fn test_pull_rebase_honours_column_owner_named_by_branch() {
+        let (_origin_dir, dest, mut clone, _) =
+            diverged_clone("id,v\n1,remote\n2,b\n", "id,v\n1,local\n2,b\n");
+        let (auth, cancel) = (RemoteAuth::default(), AtomicBool::new(false));
+        clone.create_branch("work", None).unwrap();
+        clone.checkout("work", false).unwrap();
+        clone
+            .set_merge_strategy(&MergeStrategyRule {
+                pattern: "data.csv".to_string(),
+                strategy: MergeStrategy::ColumnOwnership,
+                key_columns: Vec::new(),
+                column_owners: vec![ColumnOwner {
+                    branch: "main".to_string(),
+                    columns: vec!["v".to_string()],
+                }],
+            })
+            .unwrap();
+
+        let result = clone
+            .pull("origin", "main", true, &auth, &cancel, &mut |_| {})
+            .unwrap();
+        let rebase = result.rebase.unwrap();
+        // origin/main is upstream ("ours") while replaying, and owns the cell
+        assert!(rebase.finished, "conflicts: {:?}", rebase.conflicts);
+        assert_eq!(
+            std::fs::read_to_string(dest.path().join("data.csv")).unwrap(),
+            "id,v\n1,remote\n2,b\n"
+        );
+    }
+
+    #[test]
+    fn test_remote_management() {
+        let dir = TempDir::new().unwrap();
+        let service = GitService::init(dir.path()).unwrap();
+        service.add_remote("origin", "https://example.com/a.git").unwrap();
+
+        service.set_remote_url("origin", "https://example.com/b.git").unwrap();
+        service
+            .set_remote_push_urls(
+                "origin",
+                &["https://example.com/b.git".to_string(), "https://mirror.example.com/b.git".to_string()],
+            )
+            .unwrap();
+        let remote = &service.remotes().unwrap()[0];
+        assert_eq!(remote.url, "https://example.com/b.git");
+        assert_eq!(remote.push_urls.len(), 2);
+        assert_eq!(remote.fetch_refspecs, vec!["+refs/heads/*:refs/remotes/origin/*"]);
+
+        assert!(service.rename_remote("origin", "upstream").unwrap().is_empty());
+        let remote = &service.remotes().unwrap()[0];
+        assert_eq!(remote.name, "upstream");
+        assert_eq!(remote.fetch_refspecs, vec!["+refs/heads/*:refs/remotes/upstream/*"]);
+        assert_eq!(remote.push_urls.len(), 2);
+
+        service.set_remote_push_urls("upstream", &[]).unwrap();
+        assert!(service.remotes().unwrap()[0].push_urls.is_empty());
+        assert!(service.set_remote_url("missing", "x").is_err());
+
+        service.remove_remote("upstream").unwrap();
+        assert!(service.remotes().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_push_to_every_push_url() {
+        let auth = RemoteAuth::default();
+        let cancel = AtomicBool::new(false);
+        let seed_dir = TempDir::new().unwrap();
+        GitService::init(seed_dir.path()).unwrap();
+        let (primary, mirror) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        for bare in [&primary, &mirror] {
+            RepoBuilder::new()
+                .bare(true)
+                .clone(&seed_dir.path().to_string_lossy(), bare.path())
+                .unwrap();
         }
+        let urls: Vec<String> = [&primary, &mirror]
+            .iter()
+            .map(|d| format!("file://{}", d.path().display()))
+            .collect();
 
-        // Add HEAD
-        if let Ok(head) = self.repo.head() {
-            if let Some(oid) = head.target() {
-                map.entry(oid).or_default().push("HEAD".to_string());
-            }
+        let dest = TempDir::new().unwrap();
+        let clone = GitService::clone(&urls[0], dest.path(), None, None, &auth, &cancel, &mut |_| {})
+            .unwrap();
+        clone.set_remote_push_urls("origin", &urls).unwrap();
+        std::fs::write(dest.path().join("a.csv"), "id\n1\n").unwrap();
+        clone.commit("Add", &["a.csv".to_string()]).unwrap();
+
+        let result = clone
+            .push("origin", "main", None, &auth, &cancel, &mut |_| {})
+            .unwrap();
+        assert_eq!(result.refs.len(), 2);
+
+        let head = clone.repo.head().unwrap().target().unwrap();
+        for bare in [&primary, &mirror] {
+            let repo = Repository::open_bare(bare.path()).unwrap();
+            assert_eq!(repo.refname_to_id("refs/heads/main").unwrap(), head);
+        }
+        assert_eq!(clone.repo.refname_to_id("refs/remotes/origin/main").unwrap(), head);
+    }
+
+    #[test]
+    fn test_push_reports_failing_push_url() {
+        let auth = RemoteAuth::default();
+        let cancel = AtomicBool::new(false);
+        let seed_dir = TempDir::new().unwrap();
+        GitService::init(seed_dir.path()).unwrap();
+        let (primary, mirror) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        for bare in [&primary, &mirror] {
+            RepoBuilder::new()
+                .bare(true)
+                .clone(&seed_dir.path().to_string_lossy(), bare.path())
+                .unwrap();
         }
+        let url = |d: &TempDir| format!("file://{}", d.path().display());
+        let missing = format!("file://{}", seed_dir.path().join("missing.git").display());
 
-        map
+        let dest = TempDir::new().unwrap();
+        let clone =
+            GitService::clone(&url(&primary), dest.path(), None, None, &auth, &cancel, &mut |_| {})
+                .unwrap();
+        let base = clone.repo.head().unwrap().target().unwrap().to_string();
+        clone
+            .set_remote_push_urls("origin", &[missing.clone(), url(&primary)])
+            .unwrap();
+        std::fs::write(dest.path().join("a.csv"), "id\n1\n").unwrap();
+        clone.commit("Add", &["a.csv".to_string()]).unwrap();
+        let head = clone.repo.head().unwrap().target().unwrap();
+
+        // The missing URL doesn't stop the push to the primary
+        let result = clone
+            .push("origin", "main", None, &auth, &cancel, &mut |_| {})
+            .unwrap();
+        assert_eq!(result.refs.len(), 1);
+        assert_eq!(result.failed_urls.len(), 1);
+        assert_eq!(result.failed_urls[0].url, missing);
+        let primary_repo = Repository::open_bare(primary.path()).unwrap();
+        assert_eq!(primary_repo.refname_to_id("refs/heads/main").unwrap(), head);
+        assert_eq!(clone.repo.refname_to_id("refs/remotes/origin/main").unwrap(), head);
+
+        // A stale lease on the mirror stops the push before the primary is touched
+        let mirror_repo = Repository::open_bare(mirror.path()).unwrap();
+        let sig = git2::Signature::now("Other", "other@example.com").unwrap();
+        let tree = mirror_repo.head().unwrap().peel_to_tree().unwrap();
+        let parent = mirror_repo.head().unwrap().peel_to_commit().unwrap();
+        mirror_repo
+            .commit(Some("refs/heads/main"), &sig, &sig, "Elsewhere", &tree, &[&parent])
+            .unwrap();
+        clone
+            .set_remote_push_urls("origin", &[url(&primary), url(&mirror)])
+            .unwrap();
+        let base = clone.repo.revparse_single(&base).unwrap();
+        clone.repo.reset(&base, git2::ResetType::Hard, None).unwrap();
+        let err = clone
+            .push("origin", "main", Some(&head.to_string()), &auth, &cancel, &mut |_| {})
+            .unwrap_err();
+        assert!(matches!(err, AppError::StaleLease { .. }));
+        assert_eq!(primary_repo.refname_to_id("refs/heads/main").unwrap(), head);
     }
 
-    /// Convert a git2::Commit to our model Commit (no refs).
-    fn commit_to_model(commit: &git2::Commit) -> Commit {
-        Self::commit_to_model_with_refs(commit, Vec::new())
+    #[test]
+    fn test_revert_keeps_later_cell_edits() {
+        let dir = TempDir::new().unwrap();
+        let service = GitService::init(dir.path()).unwrap();
+        let write = |name: &str, content: &str| std::fs::write(dir.path().join(name), content).unwrap();
+        let files = ["data.csv".to_string(), "other.csv".to_string()];
+
+        write("data.csv", "id,a,b\n1,x,y\n2,p,q\n");
+        write("other.csv", "id\n1\n");
+        service.commit("Base", &files).unwrap();
+        write("data.csv", "id,a,b\n1,BAD,y\n2,p,q\n");
+        write("other.csv", "id\n1\n2\n");
+        let bad = service.commit("Bad edit", &files).unwrap();
+        write("data.csv", "id,a,b\n1,BAD,later\n2,p,q\n");
+        service.commit("Later edit", &files).unwrap();
+
+        // Only data.csv: other.csv keeps the bad commit's change
+        let result = service.revert(&bad.hash, Some("data.csv")).unwrap();
+        let commit = result.commit.unwrap();
+        assert!(commit.message.starts_with("Revert \"Bad edit\" in data.csv"));
+        let read = |name: &str| std::fs::read_to_string(dir.path().join(name)).unwrap();
+        assert_eq!(read("data.csv"), "id,a,b\n1,x,later\n2,p,q\n");
+        assert_eq!(read("other.csv"), "id\n1\n2\n");
+
+        // Whole commit: data.csv is already reverted, other.csv follows
+        let result = service.revert(&bad.hash, None).unwrap();
+        assert!(result.conflicts.is_empty());
+        assert_eq!(read("other.csv"), "id\n1\n");
+        assert_eq!(read("data.csv"), "id,a,b\n1,x,later\n2,p,q\n");
+        assert!(service.status().unwrap().clean);
+        assert_eq!(service.repo.state(), RepositoryState::Clean);
     }
 
-    /// Convert a git2::Commit to our model Commit with refs.
-    fn commit_to_model_with_refs(commit: &git2::Commit, refs: Vec<String>) -> Commit {
-        let hash = commit.id().to_string();
-        let short_hash = hash[..7.min(hash.len())].to_string();
-        let message = commit.message().unwrap_or("").to_string();
-        let author = commit.author().name().unwrap_or("Unknown").to_string();
-        let timestamp = chrono::DateTime::from_timestamp(commit.time().seconds(), 0)
-            .map(|dt| dt.to_rfc3339())
-            .unwrap_or_default();
+    #[test]
+    fn test_revert_refuses_staged_changes() {
+        let dir = TempDir::new().unwrap();
+        let service = GitService::init(dir.path()).unwrap();
+        let files = ["data.csv".to_string()];
 
-        Commit {
-            hash,
-            short_hash,
-            message,
-            author,
-            timestamp,
-            refs,
-        }
+        std::fs::write(dir.path().join("notes.csv"), "id\n1\n").unwrap();
+        service.commit("Notes", &["notes.csv".to_string()]).unwrap();
+        std::fs::write(dir.path().join("data.csv"), "id\n1\n").unwrap();
+        let added = service.commit("Add", &files).unwrap();
+        std::fs::write(dir.path().join("notes.csv"), "id\n2\n").unwrap();
+        let mut index = service.repo.index().unwrap();
+        index.add_path(Path::new("notes.csv")).unwrap();
+        index.write().unwrap();
+
+        // The staged file would be committed along with the revert
+        let err = service.revert(&added.hash, None).unwrap_err();
+        assert!(err.to_string().contains("staged changes"));
+        let err = service.revert(&added.hash, Some("data.csv")).unwrap_err();
+        assert!(err.to_string().contains("staged changes"));
+        assert_eq!(service.repo.head().unwrap().target().unwrap().to_string(), added.hash);
+        assert_eq!(service.repo.state(), RepositoryState::Clean);
+        assert!(dir.path().join("data.csv").exists());
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use tempfile::TempDir;
+    #[test]
+    fn test_revert_reports_cell_conflicts() {
+        let dir = TempDir::new().unwrap();
+        let service = GitService::init(dir.path()).unwrap();
+        let files = ["data.csv".to_string()];
+
+        std::fs::write(dir.path().join("data.csv"), "id,a\n1,x\n").unwrap();
+        service.commit("Base", &files).unwrap();
+        std::fs::write(dir.path().join("data.csv"), "id,a\n1,BAD\n").unwrap();
+        let bad = service.commit("Bad edit", &files).unwrap();
+        std::fs::write(dir.path().join("data.csv"), "id,a\n1,fixed\n").unwrap();
+        service.commit("Fix by hand", &files).unwrap();
+
+        let result = service.revert(&bad.hash, None).unwrap();
+        assert!(result.commit.is_none());
+        assert_eq!(result.conflicts, files.to_vec());
+        assert_eq!(service.repo_state().unwrap().operation, RepoOperation::Revert);
+
+        let commit = service.resolve_conflicts(&files).unwrap();
+        assert!(commit.message.starts_with("Revert \"Bad edit\""));
+        assert_eq!(service.repo.state(), RepositoryState::Clean);
+
+        // The single-file variant records the conflict the same way
+        std::fs::write(dir.path().join("data.csv"), "id,a\n1,BAD again\n").unwrap();
+        let bad = service.commit("Bad again", &files).unwrap();
+        std::fs::write(dir.path().join("data.csv"), "id,a\n1,fixed again\n").unwrap();
+        service.commit("Fix again", &files).unwrap();
+
+        let result = service.revert(&bad.hash, Some("data.csv")).unwrap();
+        assert_eq!(result.conflicts, files.to_vec());
+        assert_eq!(service.repo_state().unwrap().operation, RepoOperation::Revert);
+        service.merge_abort().unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("data.csv")).unwrap(),
+            "id,a\n1,fixed again\n"
+        );
+
+        let result = service.revert(&bad.hash, Some("data.csv")).unwrap();
+        assert_eq!(result.conflicts, files.to_vec());
+        let conflict = &service.conflicts().unwrap().files[0];
+        assert_eq!(conflict.kind, ConflictKind::BothModified);
+        let commit = service.resolve_conflicts(&files).unwrap();
+        assert!(commit.message.starts_with("Revert \"Bad again\" in data.csv"));
+    }
 
     #[test]
-    fn test_init_and_open() {
+    fn test_cherry_pick_merges_cells_and_keeps_author() {
+        let dir = TempDir::new().unwrap();
+        let mut service = GitService::init(dir.path()).unwrap();
+        let write = |name: &str, content: &str| std::fs::write(dir.path().join(name), content).unwrap();
+        let read = |name: &str| std::fs::read_to_string(dir.path().join(name)).unwrap();
+        let files = ["data.csv".to_string(), "other.csv".to_string()];
+
+        write("data.csv", "id,a,b\n1,x,y\n2,p,q\n");
+        write("other.csv", "id\n1\n");
+        service.commit("Base", &files).unwrap();
+        let main = service.current_branch().unwrap();
+        service.create_branch("fixes", None).unwrap();
+        service.checkout("fixes", false).unwrap();
+        write("data.csv", "id,a,b\n1,fixed,y\n2,p,q\n");
+        write("other.csv", "id\n1\n2\n");
+        let fix = {
+            let sig = Signature::now("Alice", "alice@example.com").unwrap();
+            let repo = &service.repo;
+            let mut index = repo.index().unwrap();
+            index.add_path(Path::new("data.csv")).unwrap();
+            index.add_path(Path::new("other.csv")).unwrap();
+            index.write().unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let parent = repo.head().unwrap().peel_to_commit().unwrap();
+            repo.commit(Some("HEAD"), &sig, &sig, "Fix row 1\n", &tree, &[&parent])
+                .unwrap()
+                .to_string()
+        };
+
+        service.checkout(&main, false).unwrap();
+        write("data.csv", "id,a,b\n1,x,main\n2,p,q\n");
+        service.commit("Main edit", &files).unwrap();
+
+        // Only data.csv, merged cell by cell with main's edit to the same row
+        let result = service.cherry_pick(&fix, &["data.csv".to_string()]).unwrap();
+        let commit = result.commit.unwrap();
+        assert!(commit.message.starts_with("Fix row 1\n\nCherry-picked data.csv"));
+        assert_eq!(commit.author, "Alice");
+        assert_eq!(read("data.csv"), "id,a,b\n1,fixed,main\n2,p,q\n");
+        assert_eq!(read("other.csv"), "id\n1\n");
+
+        // The whole commit brings the rest
+        let result = service.cherry_pick(&fix, &[]).unwrap();
+        assert_eq!(result.commit.unwrap().message, "Fix row 1\n");
+        assert_eq!(read("other.csv"), "id\n1\n2\n");
+        assert!(service.status().unwrap().clean);
+        assert_eq!(service.repo.state(), RepositoryState::Clean);
+    }
+
+    #[test]
+    fn test_cherry_pick_refuses_staged_changes() {
+        let dir = TempDir::new().unwrap();
+        let mut service = GitService::init(dir.path()).unwrap();
+        let write = |name: &str, content: &str| std::fs::write(dir.path().join(name), content).unwrap();
+
+        write("notes.csv", "id\n1\n");
+        service.commit("Notes", &["notes.csv".to_string()]).unwrap();
+        let main = service.current_branch().unwrap();
+        service.create_branch("fixes", None).unwrap();
+        service.checkout("fixes", false).unwrap();
+        write("data.csv", "id\n1\n");
+        let fix = service.commit("Add", &["data.csv".to_string()]).unwrap();
+        service.checkout(&main, false).unwrap();
+
+        write("notes.csv", "id\n2\n");
+        let mut index = service.repo.index().unwrap();
+        index.add_path(Path::new("notes.csv")).unwrap();
+        index.write().unwrap();
+        let head = service.repo.head().unwrap().target().unwrap();
+
+        // The staged file would be committed along with the pick
+        let err = service.cherry_pick(&fix.hash, &[]).unwrap_err();
+        assert!(err.to_string().contains("staged changes"));
+        let err = service.cherry_pick(&fix.hash, &["data.csv".to_string()]).unwrap_err();
+        assert!(err.to_string().contains("staged changes"));
+        assert_eq!(service.repo.head().unwrap().target().unwrap(), head);
+        assert_eq!(service.repo.state(), RepositoryState::Clean);
+        assert!(!dir.path().join("data.csv").exists());
+    }
+
+    #[test]
+    fn test_cherry_pick_conflicts_continue_and_abort() {
+        let dir = TempDir::new().unwrap();
+        let mut service = GitService::init(dir.path()).unwrap();
+        let files = ["data.csv".to_string()];
+        let path = dir.path().join("data.csv");
+
+        std::fs::write(&path, "id,a\n1,x\n").unwrap();
+        service.commit("Base", &files).unwrap();
+        let main = service.current_branch().unwrap();
+        service.create_branch("other", None).unwrap();
+        service.checkout("other", false).unwrap();
+        std::fs::write(&path, "id,a\n1,theirs\n").unwrap();
+        let pick = service.commit("Their edit", &files).unwrap();
+        service.checkout(&main, false).unwrap();
+        std::fs::write(&path, "id,a\n1,ours\n").unwrap();
+        let ours = service.commit("Our edit", &files).unwrap();
+
+        let result = service.cherry_pick(&pick.hash, &[]).unwrap();
+        assert!(result.commit.is_none());
+        assert_eq!(result.conflicts, files.to_vec());
+        assert_eq!(service.repo_state().unwrap().operation, RepoOperation::CherryPick);
+        service.merge_abort().unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "id,a\n1,ours\n");
+        assert_eq!(service.repo.head().unwrap().target().unwrap().to_string(), ours.hash);
+
+        // Selected files conflict the same way and finish like a merge
+        let result = service.cherry_pick(&pick.hash, &files).unwrap();
+        assert_eq!(result.conflicts, files.to_vec());
+        assert_eq!(service.repo_state().unwrap().operation, RepoOperation::CherryPick);
+        let commit = service.resolve_conflicts(&files).unwrap();
+        assert!(commit.message.starts_with("Their edit"));
+        assert_eq!(service.repo.state(), RepositoryState::Clean);
+    }
+
+    #[test]
+    fn test_pick_cells_from_branch() {
+        let dir = TempDir::new().unwrap();
+        let mut service = GitService::init(dir.path()).unwrap();
+        let files = ["data.csv".to_string()];
+        let path = dir.path().join("data.csv");
+
+        std::fs::write(&path, "id,a,b\n1,x,y\n2,x,y\n").unwrap();
+        service.commit("Base", &files).unwrap();
+        let main = service.current_branch().unwrap();
+        service.create_branch("colleague", None).unwrap();
+        service.checkout("colleague", false).unwrap();
+        std::fs::write(&path, "id,a,b\n1,A,B\n2,A,B\n3,new,row\n").unwrap();
+        service.commit("Their fixes", &files).unwrap();
+        service.checkout(&main, false).unwrap();
+        std::fs::write(&path, "id,a,b\n1,x,y\n2,x,local\n").unwrap();
+
+        let cell = CellRef {
+            row_key: "2".to_string(),
+            column: "a".to_string(),
+        };
+        let diff = service
+            .pick_cells("colleague", "data.csv", &["3".to_string()], &[cell])
+            .unwrap();
+        let table = diff.table.unwrap();
+        assert_eq!(table.added_rows, vec!["3"]);
+        assert_eq!(table.modified_cells.len(), 1);
+        assert_eq!(table.modified_cells[0].new_value, "A");
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "id,a,b\n1,x,y\n2,A,local\n3,new,row\n"
+        );
+        assert!(service.status().unwrap().staged.is_empty());
+
+        // CRLF files keep their line endings
+        std::fs::write(&path, "id,a,b\r\n1,x,y\r\n2,x,y\r\n").unwrap();
+        service.pick_cells("colleague", "data.csv", &["3".to_string()], &[]).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "id,a,b\r\n1,x,y\r\n2,x,y\r\n3,new,row\r\n"
+        );
+
+        // A missing working file is created with just the picked rows
+        std::fs::remove_file(&path).unwrap();
+        let diff = service
+            .pick_cells("colleague", "data.csv", &["1".to_string()], &[])
+            .unwrap();
+        assert_eq!(diff.change, FileChangeKind::Added);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "id,a,b\n1,A,B\n");
+    }
+
+    #[test]
+    fn test_amend_replaces_head_until_pushed() {
+        let origin_dir = TempDir::new().unwrap();
+        let origin = GitService::init(origin_dir.path()).unwrap();
+        std::fs::write(origin_dir.path().join("data.csv"), "id,v\n1,a\n").unwrap();
+        origin.commit("Base", &["data.csv".to_string()]).unwrap();
+        let url = format!("file://{}", origin_dir.path().display());
+        let dir = TempDir::new().unwrap();
+        let cancel = AtomicBool::new(false);
+        let service = GitService::clone(&url, dir.path(), None, None, &RemoteAuth::default(), &cancel, &mut |_| {})
+            .unwrap();
+        let files = ["data.csv".to_string(), "extra.csv".to_string()];
+        let head = service.repo.head().unwrap().peel_to_commit().unwrap();
+
+        // HEAD is on origin: amending would rewrite published history
+        let err = service.amend("Fixed typo", &[]).unwrap_err();
+        assert!(matches!(err, AppError::AlreadyPushed { .. }));
+
+        std::fs::write(dir.path().join("data.csv"), "id,v\n1,local\n").unwrap();
+        let local = service.commit("Local edti", &files[..1]).unwrap();
+        std::fs::write(dir.path().join("extra.csv"), "id\n1\n").unwrap();
+        let amended = service.amend("Local edit", &files).unwrap();
+
+        assert_ne!(amended.hash, local.hash);
+        assert_eq!(amended.message, "Local edit");
+        let commit = service.repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(commit.parent_id(0).unwrap(), head.id());
+        assert!(commit.tree().unwrap().get_path(Path::new("extra.csv")).is_ok());
+        assert!(service.status().unwrap().clean);
+
+        // An empty message keeps the existing one
+        let amended = service.amend("", &[]).unwrap();
+        assert_eq!(amended.message, "Local edit");
+    }
+
+    #[test]
+    fn test_reset_undoes_last_commit() {
         let dir = TempDir::new().unwrap();
         let service = GitService::init(dir.path()).unwrap();
+        let files = ["data.csv".to_string()];
+        let path = dir.path().join("data.csv");
+
+        std::fs::write(&path, "id,v\n1,a\n").unwrap();
+        let base = service.commit("Base", &files).unwrap();
+        std::fs::write(&path, "id,v\n1,b\n").unwrap();
+        let last = service.commit("Oops", &files).unwrap();
+
+        // Soft: the edit stays staged
+        let result = service.reset("HEAD~1", ResetMode::Soft, None).unwrap();
+        assert_eq!(result.previous, last.hash);
+        assert_eq!(result.commit.hash, base.hash);
+        let entry = result.reflog.unwrap();
+        assert_eq!(entry.old_hash, last.hash);
+        assert_eq!(entry.new_hash, base.hash);
+        assert!(entry.message.starts_with("reset: moving to"));
+        assert_eq!(service.status().unwrap().staged, files.to_vec());
+
+        // Undo via `previous`, then mixed: the edit stays in the working tree only
+        service.reset(&result.previous, ResetMode::Soft, None).unwrap();
+        service.reset("HEAD~1", ResetMode::Mixed, None).unwrap();
         let status = service.status().unwrap();
-        assert!(status.clean);
+        assert!(status.staged.is_empty());
+        assert_eq!(status.modified, files.to_vec());
 
-        let _reopened = GitService::open(dir.path()).unwrap();
+        // Hard needs the current HEAD as confirmation
+        let err = service.reset(&last.hash, ResetMode::Hard, None).unwrap_err();
+        assert!(matches!(err, AppError::ConfirmationRequired(ref head) if *head == base.hash));
+        assert!(service.reset(&last.hash, ResetMode::Hard, Some(&last.hash)).is_err());
+        service.reset(&last.hash, ResetMode::Hard, Some(&base.hash)).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "id,v\n1,b\n");
+        assert!(service.status().unwrap().clean);
     }
 
     #[test]
-    fn test_commit_and_log() {
+    fn test_reflog_summarises_and_recovers_lost_commits() {
         let dir = TempDir::new().unwrap();
         let service = GitService::init(dir.path()).unwrap();
+        let files = ["data.csv".to_string()];
+        let path = dir.path().join("data.csv");
 
-        // Create a test file
-        std::fs::write(dir.path().join("test.csv"), "a,b\n1,2\n").unwrap();
+        std::fs::write(&path, "id,v\n1,a\n").unwrap();
+        let base = service.commit("Base", &files).unwrap();
+        std::fs::write(&path, "id,v\n1,b\n2,c\n").unwrap();
+        let lost = service.commit("Lost work", &files).unwrap();
+        service.reset(&base.hash, ResetMode::Hard, Some(&lost.hash)).unwrap();
 
-        let commit = service
-            .commit("Add test file", &["test.csv".to_string()])
+        let branch = service.current_branch().unwrap();
+        for reference in ["HEAD", branch.as_str()] {
+            let entries = service.reflog(reference).unwrap();
+            // Reset, lost work, base, then the initial commit
+            assert_eq!(entries.len(), 4);
+            assert_eq!(entries[0].new_hash, base.hash);
+            assert_eq!(entries[0].summary, "data.csv: 1 row(s) removed, 1 cell(s) modified");
+            assert_eq!(entries[1].new_hash, lost.hash);
+            assert_eq!(entries[2].summary, "data.csv: 1 row(s) added, 2 column(s) added");
+        }
+
+        let recovered = service.recover_reflog_entry("HEAD", 1, "recovered").unwrap();
+        assert_eq!(recovered.hash, lost.hash);
+        assert_eq!(service.show_file("recovered", "data.csv").unwrap(), "id,v\n1,b\n2,c\n");
+        assert!(service.recover_reflog_entry("HEAD", 9, "nope").is_err());
+
+        // Paths keep their case; entries for pruned commits still list
+        std::fs::write(dir.path().join("README.TXT"), "notes\n").unwrap();
+        service.commit("Notes", &["README.TXT".to_string()]).unwrap();
+        assert_eq!(service.reflog("HEAD").unwrap()[0].summary, "README.TXT: added");
+        let mut reflog = service.repo.reflog("HEAD").unwrap();
+        let sig = Signature::now("Test", "test@example.com").unwrap();
+        let pruned = git2::Oid::from_str("1234567890123456789012345678901234567890").unwrap();
+        reflog.append(pruned, &sig, Some("pruned")).unwrap();
+        reflog.write().unwrap();
+        let entries = service.reflog("HEAD").unwrap();
+        assert_eq!(entries[0].new_hash, pruned.to_string());
+        assert_eq!(entries[0].summary, "");
+    }
+
+    #[test]
+    fn test_annotated_tags() {
+        let dir = TempDir::new().unwrap();
+        let service = GitService::init(dir.path()).unwrap();
+        let files = ["data.csv".to_string()];
+        let path = dir.path().join("data.csv");
+
+        std::fs::write(&path, "id,v\n1,q1\n").unwrap();
+        let q1 = service.commit("Q1 data", &files).unwrap();
+        let tag = service.create_tag("2024-Q1", None, "First quarter release").unwrap();
+        assert!(tag.annotated);
+        assert_eq!(tag.message.as_deref(), Some("First quarter release"));
+        assert_eq!(tag.commit.hash, q1.hash);
+
+        std::fs::write(&path, "id,v\n1,q2\n").unwrap();
+        service.commit("Q2 data", &files).unwrap();
+
+        // Tags show up in the log and work as diff endpoints and branch bases
+        let log = service.log(None, 10, 0, &LogOptions::default()).unwrap();
+        let tagged = log.iter().find(|c| c.hash == q1.hash).unwrap();
+        assert!(tagged.refs.contains(&"2024-Q1".to_string()));
+        assert_eq!(service.show_file("2024-Q1", "data.csv").unwrap(), "id,v\n1,q1\n");
+        service.create_branch("q1-fixes", Some("2024-Q1")).unwrap();
+        let branch = service.repo.find_branch("q1-fixes", BranchType::Local).unwrap();
+        assert_eq!(branch.get().target().unwrap().to_string(), q1.hash);
+
+        assert_eq!(service.tags().unwrap().len(), 1);
+        service.delete_tag("2024-Q1").unwrap();
+        assert!(service.tags().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_push_tag() {
+        let seed_dir = TempDir::new().unwrap();
+        GitService::init(seed_dir.path()).unwrap();
+        let bare_dir = TempDir::new().unwrap();
+        let origin = RepoBuilder::new()
+            .bare(true)
+            .clone(&seed_dir.path().to_string_lossy(), bare_dir.path())
+            .unwrap();
+        let url = format!("file://{}", bare_dir.path().display());
+        let dir = TempDir::new().unwrap();
+        let cancel = AtomicBool::new(false);
+        let auth = RemoteAuth::default();
+        let clone = GitService::clone(&url, dir.path(), None, None, &auth, &cancel, &mut |_| {})
             .unwrap();
-        assert_eq!(commit.message, "Add test file");
 
-        let log = service.log(None, 10, 0).unwrap();
-        assert!(log.len() >= 2); // initial + our commit
+        clone.create_tag("v1", None, "Release 1").unwrap();
+        let result = clone.push_tag("origin", "v1", &auth, &cancel, &mut |_| {}).unwrap();
+        assert_eq!(result.refs.len(), 1);
+        assert!(result.refs[0].ok);
+        let pushed = origin.revparse_single("refs/tags/v1").unwrap();
+        assert_eq!(pushed.as_tag().unwrap().message(), Some("Release 1"));
     }
 
     #[test]
-    fn test_checkout_updates_working_directory() {
+    fn test_export_release_with_manifest_and_changelog() {
         let dir = TempDir::new().unwrap();
         let service = GitService::init(dir.path()).unwrap();
+        let files = ["data.csv".to_string(), "notes.txt".to_string()];
+        let write = |name: &str, content: &str| std::fs::write(dir.path().join(name), content).unwrap();
 
-        // Create data.csv on main and commit it
-        std::fs::write(dir.path().join("data.csv"), "a,b\n1,2\n").unwrap();
-        service
-            .commit("Add data.csv on main", &["data.csv".to_string()])
+        write("data.csv", "id,qty\n1,5\n");
+        write("notes.txt", "not a table");
+        service.commit("Q1", &files).unwrap();
+        service.create_tag("q1", None, "Q1 release").unwrap();
+        write("data.csv", "id,qty\n1,6\n2,3\n");
+        service.commit("Q2", &files[..1]).unwrap();
+        service.create_tag("q2", None, "Q2 release").unwrap();
+
+        let dest = dir.path().join("out/q2.zip");
+        let manifest = service.export_release("q2", &dest).unwrap();
+        assert_eq!(manifest.previous_tag.as_deref(), Some("q1"));
+        assert_eq!(manifest.files.len(), 1);
+        let file = &manifest.files[0];
+        assert_eq!((file.path.as_str(), file.rows), ("data.csv", 2));
+        assert_eq!(file.columns[1].field, "qty");
+        assert_eq!(file.sha256, release::sha256_hex(b"id,qty\n1,6\n2,3\n"));
+        let table = manifest.changelog[0].table.as_ref().unwrap();
+        assert_eq!(table.summary, "1 row(s) added, 1 cell(s) modified");
+
+        let archive = zip::ZipArchive::new(std::fs::File::open(&dest).unwrap()).unwrap();
+        let mut names: Vec<&str> = archive.file_names().collect();
+        names.sort();
+        assert_eq!(names, vec!["q2/CHANGELOG.md", "q2/data.csv", "q2/manifest.json"]);
+
+        // The first release has no previous tag and lists every table as added
+        let manifest = service.export_release("q1", &dir.path().join("q1.tar.gz")).unwrap();
+        assert!(manifest.previous_tag.is_none());
+        assert_eq!(manifest.changelog[0].change, FileChangeKind::Added);
+        assert!(service.export_release("q1", &dir.path().join("q1.rar")).is_err());
+    }
+
+    #[test]
+    fn test_bundle_export_and_fetch() {
+        let office_dir = TempDir::new().unwrap();
+        let office = GitService::init(office_dir.path()).unwrap();
+        let files = ["data.csv".to_string()];
+        std::fs::write(office_dir.path().join("data.csv"), "id,a,b\n1,x,y\n").unwrap();
+        let base = office.commit("Base", &files).unwrap();
+        let main = office.current_branch().unwrap();
+
+        // The field team starts from a full bundle
+        let full = office_dir.path().join("full.bundle");
+        let info = office.export_bundle(&full, &[], None).unwrap();
+        assert_eq!(info.refs, vec![format!("refs/heads/{}", main)]);
+        assert!(info.prerequisites.is_empty());
+        let field_dir = TempDir::new().unwrap();
+        let field = GitService::init(field_dir.path()).unwrap();
+        let result = field.fetch_bundle(&full, "office").unwrap();
+        assert_eq!(result.updated[0].name, format!("refs/remotes/office/{}", main));
+        let initial = field.repo.head().unwrap().target().unwrap().to_string();
+        field.reset(&base.hash, ResetMode::Hard, Some(&initial)).unwrap();
+
+        // Both sides edit different cells of the same row
+        std::fs::write(office_dir.path().join("data.csv"), "id,a,b\n1,office,y\n").unwrap();
+        office.commit("Office edit", &files).unwrap();
+        std::fs::write(field_dir.path().join("data.csv"), "id,a,b\n1,x,field\n").unwrap();
+        field.commit("Field edit", &files).unwrap();
+
+        let incremental = office_dir.path().join("since.bundle");
+        let info = office
+            .export_bundle(&incremental, std::slice::from_ref(&main), Some(&base.hash))
             .unwrap();
+        assert_eq!(info.prerequisites, vec![base.hash.clone()]);
+        assert!(office.export_bundle(&incremental, &[], Some(&main)).is_err());
 
-        // Create a new branch "feature" and switch to it
+        // A repository without the base commit can't use it
+        let other_dir = TempDir::new().unwrap();
+        let other = GitService::init(other_dir.path()).unwrap();
+        let err = other.fetch_bundle(&incremental, "office").unwrap_err();
+        assert!(matches!(err, AppError::MissingPrerequisites(ref m) if *m == vec![base.hash.clone()]));
+
+        field.fetch_bundle(&incremental, "office").unwrap();
+        let result = field.merge(&format!("office/{}", main)).unwrap();
+        assert!(result.success);
+        assert_eq!(
+            std::fs::read_to_string(field_dir.path().join("data.csv")).unwrap(),
+            "id,a,b\n1,office,field\n"
+        );
+    }
+
+    #[test]
+    fn test_log_graph_across_branches() {
+        let dir = TempDir::new().unwrap();
+        let mut service = GitService::init(dir.path()).unwrap();
+        let files = ["data.csv".to_string()];
+        let path = dir.path().join("data.csv");
+
+        std::fs::write(&path, "id,a,b\n1,x,y\n").unwrap();
+        let base = service.commit("Base", &files).unwrap();
+        let main = service.current_branch().unwrap();
         service.create_branch("feature", None).unwrap();
-        service.checkout("feature").unwrap();
+        service.checkout("feature", false).unwrap();
+        std::fs::write(&path, "id,a,b\n1,x,feature\n").unwrap();
+        let feature = service.commit("Feature edit", &files).unwrap();
+        service.checkout(&main, false).unwrap();
+        std::fs::write(&path, "id,a,b\n1,main,y\n").unwrap();
+        let on_main = service.commit("Main edit", &files).unwrap();
 
-        // Modify the file on feature branch and commit
-        std::fs::write(dir.path().join("data.csv"), "a,b\n3,4\n").unwrap();
-        service
-            .commit("Update data.csv on feature", &["data.csv".to_string()])
+        // HEAD only: the feature commit is not reachable
+        let log = service.log(None, 10, 0, &LogOptions::default()).unwrap();
+        assert!(log.iter().all(|c| c.hash != feature.hash));
+        assert_eq!(log[0].parents, vec![base.hash.clone()]);
+        assert_eq!(log[0].author_email, log[0].committer_email);
+
+        let options = LogOptions {
+            all: true,
+            topo: true,
+            ..Default::default()
+        };
+        let log = service.log(None, 10, 0, &options).unwrap();
+        let find = |hash: &str| log.iter().find(|c| c.hash == hash).unwrap();
+        let lane = |hash: &str| find(hash).graph.as_ref().unwrap().lane;
+        assert_ne!(lane(&feature.hash), lane(&on_main.hash));
+        assert_eq!(find(&base.hash).graph.as_ref().unwrap().joining.len(), 1);
+        assert!(find(&feature.hash).refs.contains(&"feature".to_string()));
+
+        // Selected refs, and pages keep the lanes of the full walk
+        let options = LogOptions {
+            refs: vec!["feature".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(service.log(None, 10, 0, &options).unwrap()[0].hash, feature.hash);
+        let all = LogOptions {
+            all: true,
+            topo: true,
+            ..Default::default()
+        };
+        let page = service.log(None, 1, 2, &all).unwrap();
+        let full = service.log(None, 10, 0, &all).unwrap();
+        assert_eq!(page[0].graph, full[2].graph);
+
+        // A file filter drops the graph, which would have gaps
+        let log = service.log(Some("data.csv"), 10, 0, &all).unwrap();
+        assert!(log.iter().all(|c| c.graph.is_none()));
+
+        // A parent committed with a clock ahead of its children still comes
+        // after them, or the lanes would break
+        let repo = &service.repo;
+        let tree = repo.head().unwrap().peel_to_tree().unwrap();
+        let commit = |time: i64, parent: &git2::Commit, branch: &str| {
+            let when = git2::Time::new(time, 0);
+            let sig = Signature::new("Test", "test@example.com", &when).unwrap();
+            let refname = format!("refs/heads/{}", branch);
+            let oid = repo
+                .commit(Some(&refname), &sig, &sig, branch, &tree, &[parent])
+                .unwrap();
+            repo.find_commit(oid).unwrap()
+        };
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        let skewed = commit(head.time().seconds() + 1_000_000, &head, "skewed");
+        let early = commit(head.time().seconds() + 10, &skewed, "early");
+        commit(head.time().seconds() + 20, &skewed, "late");
+        let all = LogOptions {
+            all: true,
+            ..Default::default()
+        };
+        let log = service.log(None, 20, 0, &all).unwrap();
+        let position = |oid: git2::Oid| log.iter().position(|c| c.hash == oid.to_string()).unwrap();
+        assert!(position(early.id()) < position(skewed.id()));
+    }
+
+    #[test]
+    fn test_commit_details() {
+        let dir = TempDir::new().unwrap();
+        let service = GitService::init(dir.path()).unwrap();
+        let files = ["data.csv".to_string(), "notes.txt".to_string()];
+        std::fs::write(dir.path().join("data.csv"), "id,a,b\n1,x,y\n2,x,y\n").unwrap();
+        service.commit("Base", &files[..1]).unwrap();
+
+        std::fs::write(dir.path().join("data.csv"), "id,a,b\n1,X,Y\n3,n,n\n").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "hello").unwrap();
+        let (hash, parent) = {
+            let repo = &service.repo;
+            let mut index = repo.index().unwrap();
+            index.add_path(Path::new("data.csv")).unwrap();
+            index.add_path(Path::new("notes.txt")).unwrap();
+            index.write().unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let parent = repo.head().unwrap().peel_to_commit().unwrap();
+            let author = Signature::new(
+                "Ana",
+                "ana@example.com",
+                &git2::Time::new(1_709_281_800, 60),
+            )
             .unwrap();
+            let committer = Signature::new(
+                "Ben",
+                "ben@example.com",
+                &git2::Time::new(1_709_300_000, -300),
+            )
+            .unwrap();
+            let message = "Fix Q1 figures\n\nRow 2 was a duplicate.\n\n\
+                           Co-authored-by: Cy <cy@example.com>\n\
+                           Signed-off-by: Ana <ana@example.com>\n";
+            let oid = repo
+                .commit(Some("HEAD"), &author, &committer, message, &tree, &[&parent])
+                .unwrap();
+            (oid.to_string(), parent.id().to_string())
+        };
 
-        // Switch back to main
-        service.checkout("main").unwrap();
-        let content = std::fs::read_to_string(dir.path().join("data.csv")).unwrap();
-        assert_eq!(content, "a,b\n1,2\n", "checkout main should restore original file content");
+        let details = service.commit_details(&hash).unwrap();
+        assert_eq!(details.subject, "Fix Q1 figures");
+        assert!(details.body.starts_with("Row 2 was a duplicate."));
+        assert_eq!(details.trailers.len(), 2);
+        assert_eq!(details.trailers[0].key, "Co-authored-by");
+        assert_eq!(details.trailers[1].value, "Ana <ana@example.com>");
+        assert_eq!(details.author.email, "ana@example.com");
+        assert_eq!(details.author.timestamp, "2024-03-01T09:30:00+01:00");
+        assert_eq!(details.committer.offset_minutes, -300);
+        assert_eq!(details.parents, vec![parent]);
 
-        // Switch to feature again
-        service.checkout("feature").unwrap();
-        let content = std::fs::read_to_string(dir.path().join("data.csv")).unwrap();
-        assert_eq!(content, "a,b\n3,4\n", "checkout feature should restore modified file content");
+        let data = details.files.iter().find(|f| f.path == "data.csv").unwrap();
+        assert_eq!((data.rows_added, data.rows_removed), (1, 1));
+        assert_eq!((data.rows_modified, data.cells_changed), (1, 2));
+        let notes = details.files.iter().find(|f| f.path == "notes.txt").unwrap();
+        assert_eq!(notes.change, FileChangeKind::Added);
+        assert_eq!(notes.cells_changed, 0);
+    }
+
+    #[test]
+    fn test_restore_file_warns_about_uncommitted_changes() {
+        let dir = TempDir::new().unwrap();
+        let service = GitService::init(dir.path()).unwrap();
+        let files = ["data.csv".to_string()];
+        let path = dir.path().join("data.csv");
+
+        std::fs::write(&path, "id,v\n1,old\n").unwrap();
+        let old = service.commit("Old", &files).unwrap();
+        std::fs::write(&path, "id,v\n1,new\n2,added\n").unwrap();
+        service.commit("New", &files).unwrap();
+
+        // Clean file: restored straight away, left unstaged
+        let result = service.restore_file("data.csv", &old.hash, false, false).unwrap();
+        assert!(result.restored && !result.uncommitted && !result.staged);
+        let table = result.changes.table.unwrap();
+        assert_eq!(table.removed_rows, vec!["2"]);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "id,v\n1,old\n");
+        assert_eq!(service.status().unwrap().modified, files.to_vec());
+
+        // Now it has uncommitted changes, so it needs force
+        std::fs::write(&path, "id,v\n1,draft\n").unwrap();
+        let head = service.repo.head().unwrap().target().unwrap().to_string();
+        let result = service.restore_file("data.csv", &head, true, false).unwrap();
+        assert!(!result.restored && result.uncommitted);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "id,v\n1,draft\n");
+
+        let result = service.restore_file("data.csv", &head, true, true).unwrap();
+        assert!(result.restored && result.staged);
+        assert!(service.status().unwrap().clean);
+
+        assert!(matches!(
+            service.restore_file("missing.csv", &head, false, false),
+            Err(AppError::FileNotFound(_))
+        ));
     }
 }
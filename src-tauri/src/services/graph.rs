@@ -0,0 +1,135 @@
+use git2::Oid;
+
+use crate::models::git::GraphRow;
+
+/// Assigns history graph lanes to commits fed in display order (children
+/// before parents). Each lane holds the commit its line is heading for.
+#[derive(Debug, Default)]
+pub struct Lanes {
+    active: Vec<Option<Oid>>,
+}
+
+impl Lanes {
+    /// Place the next commit and route its parent lines.
+    pub fn next(&mut self, oid: Oid, parents: &[Oid]) -> GraphRow {
+        let expecting: Vec<usize> = self.lanes_heading_for(oid);
+        let lane = match expecting.first() {
+            Some(&lane) => lane,
+            None => self.free_lane(),
+        };
+        let joining = expecting.get(1..).unwrap_or_default().to_vec();
+        for &other in &joining {
+            self.active[other] = None;
+        }
+        self.active[lane] = None;
+
+        let through = self
+            .active
+            .iter()
+            .enumerate()
+            .filter(|(_, target)| target.is_some())
+            .map(|(i, _)| i)
+            .collect();
+
+        // The first parent carries on down this lane; lines of a branch
+        // meet the lines they forked from at the fork point (`joining`)
+        let mut parent_lanes = Vec::new();
+        for (n, parent) in parents.iter().enumerate() {
+            let target = if n == 0 {
+                lane
+            } else if let Some(&existing) = self.lanes_heading_for(*parent).first() {
+                // A merged parent another line already heads for is drawn into it
+                existing
+            } else {
+                self.free_lane()
+            };
+            self.active[target] = Some(*parent);
+            parent_lanes.push(target);
+        }
+
+        while self.active.last() == Some(&None) {
+            self.active.pop();
+        }
+
+        GraphRow {
+            lane,
+            through,
+            joining,
+            parent_lanes,
+        }
+    }
+
+    fn lanes_heading_for(&self, oid: Oid) -> Vec<usize> {
+        self.active
+            .iter()
+            .enumerate()
+            .filter(|(_, target)| **target == Some(oid))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    fn free_lane(&mut self) -> usize {
+        match self.active.iter().position(|target| target.is_none()) {
+            Some(lane) => lane,
+            None => {
+                self.active.push(None);
+                self.active.len() - 1
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn oid(n: u8) -> Oid {
+        Oid::from_bytes(&[n; 20]).unwrap()
+    }
+
+    #[test]
+    fn test_linear_history_stays_in_one_lane() {
+        let mut lanes = Lanes::default();
+        let row = lanes.next(oid(3), &[oid(2)]);
+        assert_eq!((row.lane, row.parent_lanes), (0, vec![0]));
+        let row = lanes.next(oid(2), &[oid(1)]);
+        assert_eq!((row.lane, row.parent_lanes), (0, vec![0]));
+        let row = lanes.next(oid(1), &[]);
+        assert_eq!(row.lane, 0);
+        assert!(row.parent_lanes.is_empty() && row.through.is_empty());
+    }
+
+    #[test]
+    fn test_merge_opens_and_closes_a_lane() {
+        // 5 merges 4 (main) and 3 (feature); both fork from 1
+        let mut lanes = Lanes::default();
+        let merge = lanes.next(oid(5), &[oid(4), oid(3)]);
+        assert_eq!((merge.lane, merge.parent_lanes), (0, vec![0, 1]));
+
+        let main = lanes.next(oid(4), &[oid(1)]);
+        assert_eq!((main.lane, main.through), (0, vec![1]));
+
+        let feature = lanes.next(oid(3), &[oid(2)]);
+        assert_eq!((feature.lane, feature.through), (1, vec![0]));
+        let feature = lanes.next(oid(2), &[oid(1)]);
+        assert_eq!((feature.lane, feature.parent_lanes), (1, vec![1]));
+
+        // The feature line ends where it forked
+        let root = lanes.next(oid(1), &[]);
+        assert_eq!((root.lane, root.joining), (0, vec![1]));
+        assert!(root.through.is_empty());
+    }
+
+    #[test]
+    fn test_freed_lanes_are_reused() {
+        let mut lanes = Lanes::default();
+        lanes.next(oid(4), &[oid(1)]);
+        let tip = lanes.next(oid(3), &[oid(1)]);
+        assert_eq!(tip.lane, 1);
+        lanes.next(oid(1), &[]);
+
+        // Lane 1 closed at the fork, so an unrelated tip starts in lane 0
+        let unrelated = lanes.next(oid(9), &[]);
+        assert_eq!(unrelated.lane, 0);
+    }
+}
@@ -0,0 +1,285 @@
+use crate::models::diff::CellConflict;
+use crate::models::git::{ConflictFile, ConflictKind, ConflictSide, MergeStrategy, MergeStrategyRule};
+use crate::services::table_diff::{self, Table, TableMerge};
+use crate::utils::glob;
+
+/// Git config section holding the rules, one subsection per file pattern:
+///
+/// ```text
+/// [ledgit-merge "logs/*.csv"]
+///     strategy = union
+///     key = id
+/// [ledgit-merge "prices.csv"]
+///     strategy = column_ownership
+///     owner = pricing:price,currency
+/// ```
+pub const CONFIG_SECTION: &str = "ledgit-merge";
+
+/// What a merge strategy settled a file to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Merged {
+    /// New content from a table merge.
+    Text(String),
+    /// One side's blob, taken byte for byte.
+    Blob(git2::Oid),
+    /// The chosen side does not have the file.
+    Deleted,
+}
+
+/// How a single file was settled by a merge strategy.
+#[derive(Debug, Clone)]
+pub struct Resolution {
+    pub merged: Merged,
+    /// Cells still needing a decision. When non-empty, `merged` is a
+    /// provisional table that keeps ours for those cells.
+    pub conflicts: Vec<CellConflict>,
+}
+
+/// Config spelling of a strategy.
+pub fn strategy_name(strategy: MergeStrategy) -> &'static str {
+    match strategy {
+        MergeStrategy::Cells => "cells",
+        MergeStrategy::Ours => "ours",
+        MergeStrategy::Theirs => "theirs",
+        MergeStrategy::Union => "union",
+        MergeStrategy::ColumnOwnership => "column_ownership",
+    }
+}
+
+/// Parse the config spelling of a strategy.
+pub fn parse_strategy(name: &str) -> Option<MergeStrategy> {
+    match name.trim().to_lowercase().as_str() {
+        "cells" => Some(MergeStrategy::Cells),
+        "ours" => Some(MergeStrategy::Ours),
+        "theirs" => Some(MergeStrategy::Theirs),
+        "union" => Some(MergeStrategy::Union),
+        "column_ownership" | "columns" => Some(MergeStrategy::ColumnOwnership),
+        _ => None,
+    }
+}
+
+/// The first rule whose pattern matches the path.
+pub fn rule_for<'a>(rules: &'a [MergeStrategyRule], path: &str) -> Option<&'a MergeStrategyRule> {
+    rules.iter().find(|rule| glob::matches(&rule.pattern, path))
+}
+
+/// Settle a file both sides changed, using its rule or, for tables without
+/// a rule, the cell-level merge. `our_branch`/`their_branch` name the two
+/// sides for column ownership. Returns `None` when the file cannot be
+/// merged this way (not a table, unparseable, or a rename).
+pub fn resolve(
+    file: &ConflictFile,
+    rule: Option<&MergeStrategyRule>,
+    our_branch: Option<&str>,
+    their_branch: Option<&str>,
+) -> Option<Resolution> {
+    if file.kind == ConflictKind::Renamed {
+        return None;
+    }
+
+    let strategy = rule.map(|r| r.strategy).unwrap_or_default();
+    match strategy {
+        MergeStrategy::Ours => return take_side(&file.ours),
+        MergeStrategy::Theirs => return take_side(&file.theirs),
+        _ => {}
+    }
+
+    if !table_diff::is_table_path(&file.path) {
+        return None;
+    }
+
+    let parse = |side: &Option<ConflictSide>| -> Option<Table> {
+        Table::parse(side.as_ref()?.content.as_deref()?, &file.path).ok()
+    };
+    let original = file.ours.as_ref()?.content.as_deref()?;
+    let ours = parse(&file.ours)?;
+    let theirs = parse(&file.theirs)?;
+    let key_columns = rule.map(|r| r.key_columns.clone()).unwrap_or_default();
+
+    let ancestor = match &file.ancestor {
+        Some(_) => parse(&file.ancestor)?,
+        None => Table::empty(ours.delimiter),
+    };
+    let merge = if strategy == MergeStrategy::Union {
+        TableMerge {
+            table: table_diff::union_tables(&ancestor, &ours, &theirs, &key_columns),
+            conflicts: Vec::new(),
+        }
+    } else {
+        let mut merge = table_diff::merge_tables(&ancestor, &ours, &theirs, &key_columns);
+
+        if strategy == MergeStrategy::ColumnOwnership {
+            for owner in rule.map(|r| r.column_owners.as_slice()).unwrap_or_default() {
+                let side = if Some(owner.branch.as_str()) == our_branch {
+                    &ours
+                } else if Some(owner.branch.as_str()) == their_branch {
+                    &theirs
+                } else {
+                    continue;
+                };
+                table_diff::apply_column_owner(&mut merge, side, &owner.columns, &key_columns);
+            }
+        }
+        merge
+    };
+
+    Some(Resolution {
+        merged: Merged::Text(merge.table.to_csv_like(original).ok()?),
+        conflicts: merge.conflicts,
+    })
+}
+
+/// Take one side's blob wholesale (absent side → delete).
+pub fn take_side(side: &Option<ConflictSide>) -> Option<Resolution> {
+    let merged = match side {
+        Some(side) => Merged::Blob(git2::Oid::from_str(&side.oid).ok()?),
+        None => Merged::Deleted,
+    };
+    Some(Resolution {
+        merged,
+        conflicts: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::git::ColumnOwner;
+
+    fn side(content: &str) -> Option<ConflictSide> {
+        Some(ConflictSide {
+            path: "data.csv".to_string(),
+            oid: blob_id(content.as_bytes()).to_string(),
+            content: Some(content.to_string()),
+        })
+    }
+
+    fn blob_id(content: &[u8]) -> git2::Oid {
+        git2::Oid::hash_object(git2::ObjectType::Blob, content).unwrap()
+    }
+
+    fn text(content: &str) -> Merged {
+        Merged::Text(content.to_string())
+    }
+
+    fn file() -> ConflictFile {
+        ConflictFile {
+            path: "data.csv".to_string(),
+            kind: ConflictKind::BothModified,
+            ancestor: side("id,price,note\n1,10,a\n"),
+            ours: side("id,price,note\n1,12,ours\n"),
+            theirs: side("id,price,note\n1,11,theirs\n2,5,new\n"),
+        }
+    }
+
+    fn rule(strategy: MergeStrategy) -> MergeStrategyRule {
+        MergeStrategyRule {
+            pattern: "*.csv".to_string(),
+            strategy,
+            key_columns: Vec::new(),
+            column_owners: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_strategy_names_roundtrip() {
+        for strategy in [
+            MergeStrategy::Cells,
+            MergeStrategy::Ours,
+            MergeStrategy::Theirs,
+            MergeStrategy::Union,
+            MergeStrategy::ColumnOwnership,
+        ] {
+            assert_eq!(parse_strategy(strategy_name(strategy)), Some(strategy));
+        }
+        assert_eq!(parse_strategy("bogus"), None);
+    }
+
+    #[test]
+    fn test_default_cell_merge_reports_conflicts() {
+        let resolution = resolve(&file(), None, None, None).unwrap();
+        assert_eq!(resolution.conflicts.len(), 2);
+        assert_eq!(
+            resolution.merged,
+            text("id,price,note\n1,12,ours\n2,5,new\n")
+        );
+    }
+
+    #[test]
+    fn test_cell_merge_keeps_crlf() {
+        let mut file = file();
+        for side in [&mut file.ancestor, &mut file.ours, &mut file.theirs] {
+            let content = side
+                .as_ref()
+                .unwrap()
+                .content
+                .as_ref()
+                .unwrap()
+                .replace('\n', "\r\n");
+            *side = self::side(&content);
+        }
+        let resolution = resolve(&file, None, None, None).unwrap();
+        assert_eq!(
+            resolution.merged,
+            text("id,price,note\r\n1,12,ours\r\n2,5,new\r\n")
+        );
+    }
+
+    #[test]
+    fn test_ours_and_theirs() {
+        let ours = resolve(&file(), Some(&rule(MergeStrategy::Ours)), None, None).unwrap();
+        assert_eq!(
+            ours.merged,
+            Merged::Blob(blob_id(b"id,price,note\n1,12,ours\n"))
+        );
+
+        let theirs = resolve(&file(), Some(&rule(MergeStrategy::Theirs)), None, None).unwrap();
+        assert!(theirs.conflicts.is_empty());
+        assert_eq!(
+            theirs.merged,
+            Merged::Blob(blob_id(b"id,price,note\n1,11,theirs\n2,5,new\n"))
+        );
+
+        // Non-UTF-8 content is taken by blob, and only a missing side deletes
+        let mut file = file();
+        file.ours.as_mut().unwrap().content = None;
+        file.theirs = None;
+        let ours = resolve(&file, Some(&rule(MergeStrategy::Ours)), None, None).unwrap();
+        assert!(matches!(ours.merged, Merged::Blob(_)));
+        let theirs = resolve(&file, Some(&rule(MergeStrategy::Theirs)), None, None).unwrap();
+        assert_eq!(theirs.merged, Merged::Deleted);
+    }
+
+    #[test]
+    fn test_column_ownership() {
+        let mut rule = rule(MergeStrategy::ColumnOwnership);
+        rule.column_owners = vec![
+            ColumnOwner {
+                branch: "pricing".to_string(),
+                columns: vec!["price".to_string()],
+            },
+            ColumnOwner {
+                branch: "main".to_string(),
+                columns: vec!["note".to_string()],
+            },
+        ];
+
+        let resolution = resolve(&file(), Some(&rule), Some("main"), Some("pricing")).unwrap();
+        assert!(resolution.conflicts.is_empty());
+        assert_eq!(
+            resolution.merged,
+            text("id,price,note\n1,11,ours\n2,5,new\n")
+        );
+    }
+
+    #[test]
+    fn test_rule_for_uses_first_match() {
+        let mut logs = rule(MergeStrategy::Union);
+        logs.pattern = "logs/*.csv".to_string();
+        let rules = vec![logs, rule(MergeStrategy::Ours)];
+
+        assert_eq!(rule_for(&rules, "logs/a.csv").unwrap().strategy, MergeStrategy::Union);
+        assert_eq!(rule_for(&rules, "b.csv").unwrap().strategy, MergeStrategy::Ours);
+        assert!(rule_for(&rules, "notes.txt").is_none());
+    }
+}
@@ -1,2 +1,8 @@
+pub mod bundle;
 pub mod file_service;
 pub mod git_service;
+pub mod graph;
+pub mod merge_strategy;
+pub mod release;
+pub mod table_diff;
+pub mod transfer;
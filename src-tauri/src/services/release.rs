@@ -0,0 +1,195 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use chrono::{Datelike, Timelike};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use sha2::{Digest, Sha256};
+use zip::write::SimpleFileOptions;
+
+use crate::models::error::AppError;
+use crate::models::git::ReleaseManifest;
+
+/// Archive types a release can be exported as, chosen by file extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Zip,
+    TarGz,
+}
+
+impl ArchiveFormat {
+    pub fn from_path(path: &Path) -> Result<Self, AppError> {
+        let name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        if name.ends_with(".zip") {
+            Ok(Self::Zip)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Ok(Self::TarGz)
+        } else {
+            Err(AppError::UnsupportedArchive(path.display().to_string()))
+        }
+    }
+}
+
+/// Lowercase hex SHA-256 of `bytes`.
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Write `entries` (archive path, contents) to `dest`. Entries get the same
+/// `mtime` so exporting a tag twice produces the same archive contents. Zip
+/// stores it in UTC to two seconds, between 1980 and 2107.
+pub fn write_archive(
+    dest: &Path,
+    format: ArchiveFormat,
+    entries: &[(String, Vec<u8>)],
+    mtime: i64,
+) -> Result<(), AppError> {
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let file = File::create(dest)?;
+
+    match format {
+        ArchiveFormat::Zip => {
+            let mut writer = zip::ZipWriter::new(file);
+            let options = SimpleFileOptions::default()
+                .compression_method(zip::CompressionMethod::Deflated)
+                .last_modified_time(zip_time(mtime));
+            for (path, contents) in entries {
+                writer
+                    .start_file(path.as_str(), options)
+                    .map_err(zip_error)?;
+                writer.write_all(contents)?;
+            }
+            writer.finish().map_err(zip_error)?;
+        }
+        ArchiveFormat::TarGz => {
+            let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+            for (path, contents) in entries {
+                let mut header = tar::Header::new_gnu();
+                header.set_size(contents.len() as u64);
+                header.set_mode(0o644);
+                header.set_mtime(mtime.max(0) as u64);
+                header.set_cksum();
+                builder.append_data(&mut header, path, contents.as_slice())?;
+            }
+            builder.into_inner()?.finish()?;
+        }
+    }
+
+    Ok(())
+}
+
+/// `mtime` as a zip timestamp. Times zip can't store become 1980-01-01.
+fn zip_time(mtime: i64) -> zip::DateTime {
+    let time = chrono::DateTime::from_timestamp(mtime, 0).unwrap_or_default();
+    zip::DateTime::from_date_and_time(
+        u16::try_from(time.year()).unwrap_or(0),
+        time.month() as u8,
+        time.day() as u8,
+        time.hour() as u8,
+        time.minute() as u8,
+        time.second() as u8,
+    )
+    .unwrap_or_default()
+}
+
+/// Markdown changelog of a release: one section per changed table.
+pub fn changelog_markdown(manifest: &ReleaseManifest) -> String {
+    let mut text = match &manifest.previous_tag {
+        Some(previous) => format!("# {}\n\nChanges since {}.\n", manifest.tag, previous),
+        None => format!("# {}\n\nFirst release.\n", manifest.tag),
+    };
+
+    if manifest.changelog.is_empty() {
+        text.push_str("\nNo table changes.\n");
+    }
+    for file in &manifest.changelog {
+        let summary = match &file.table {
+            Some(table) => table.summary.clone(),
+            None => format!("{:?}", file.change).to_lowercase(),
+        };
+        text.push_str(&format!("\n## {}\n\n{}\n", file.path, summary));
+        if let Some(table) = &file.table {
+            for key in &table.added_rows {
+                text.push_str(&format!("- Added row {}\n", key));
+            }
+            for key in &table.removed_rows {
+                text.push_str(&format!("- Removed row {}\n", key));
+            }
+            for cell in &table.modified_cells {
+                text.push_str(&format!(
+                    "- {} / {}: {} → {}\n",
+                    cell.row_key, cell.column, cell.old_value, cell.new_value
+                ));
+            }
+        }
+    }
+
+    text
+}
+
+fn zip_error(err: zip::result::ZipError) -> AppError {
+    AppError::IoError(std::io::Error::other(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_format_from_extension() {
+        let format = |name: &str| ArchiveFormat::from_path(Path::new(name)).ok();
+        assert_eq!(format("q1.ZIP"), Some(ArchiveFormat::Zip));
+        assert_eq!(format("q1.tar.gz"), Some(ArchiveFormat::TarGz));
+        assert_eq!(format("q1.tgz"), Some(ArchiveFormat::TarGz));
+        assert_eq!(format("q1.rar"), None);
+    }
+
+    #[test]
+    fn test_sha256_hex() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_archives_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let entries = vec![("v1/data.csv".to_string(), b"id\n1\n".to_vec())];
+
+        let zip_path = dir.path().join("out/v1.zip");
+        write_archive(&zip_path, ArchiveFormat::Zip, &entries, 1_700_000_000).unwrap();
+        let mut archive = zip::ZipArchive::new(File::open(&zip_path).unwrap()).unwrap();
+        let mut file = archive.by_name("v1/data.csv").unwrap();
+        let mut content = String::new();
+        file.read_to_string(&mut content).unwrap();
+        assert_eq!(content, "id\n1\n");
+        // 2023-11-14 22:13:20 UTC
+        let time = file.last_modified().unwrap();
+        assert_eq!((time.year(), time.month(), time.day()), (2023, 11, 14));
+        assert_eq!((time.hour(), time.minute(), time.second()), (22, 13, 20));
+
+        let tar_path = dir.path().join("v1.tar.gz");
+        write_archive(&tar_path, ArchiveFormat::TarGz, &entries, 1_700_000_000).unwrap();
+        let gz = flate2::read::GzDecoder::new(File::open(&tar_path).unwrap());
+        let mut archive = tar::Archive::new(gz);
+        let mut entry = archive.entries().unwrap().next().unwrap().unwrap();
+        assert_eq!(entry.path().unwrap().to_str(), Some("v1/data.csv"));
+        assert_eq!(entry.header().mtime().unwrap(), 1_700_000_000);
+        let mut content = String::new();
+        entry.read_to_string(&mut content).unwrap();
+        assert_eq!(content, "id\n1\n");
+    }
+}
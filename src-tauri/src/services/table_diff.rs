@@ -0,0 +1,785 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::models::diff::{CellChange, CellConflict, CellRef, TableDiff};
+use crate::models::error::AppError;
+
+/// A parsed CSV/TSV table: the header row plus raw string cells.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    pub delimiter: u8,
+}
+
+/// Outcome of a three-way table merge. Conflicting cells take the "ours"
+/// value in `table` so it can be written out as a provisional result.
+#[derive(Debug, Clone)]
+pub struct TableMerge {
+    pub table: Table,
+    pub conflicts: Vec<CellConflict>,
+}
+
+impl Table {
+    /// An empty table with no columns, used for the missing side of an
+    /// added or deleted file.
+    pub fn empty(delimiter: u8) -> Self {
+        Self {
+            headers: Vec::new(),
+            rows: Vec::new(),
+            delimiter,
+        }
+    }
+
+    /// Parse CSV/TSV content. The first record is the header row.
+    pub fn parse(content: &str, file_path: &str) -> Result<Self, AppError> {
+        let delimiter = detect_delimiter(content, file_path);
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(content.as_bytes());
+
+        let mut records = reader.records();
+        let headers: Vec<String> = match records.next() {
+            Some(record) => record?.iter().map(|h| h.to_string()).collect(),
+            None => return Ok(Self::empty(delimiter)),
+        };
+
+        let mut rows = Vec::new();
+        for record in records {
+            let record = record?;
+            rows.push(record.iter().map(|c| c.to_string()).collect());
+        }
+
+        Ok(Self {
+            headers,
+            rows,
+            delimiter,
+        })
+    }
+
+    /// Serialize back to CSV/TSV text, padding short rows to the header width.
+    pub fn to_csv(&self) -> Result<String, AppError> {
+        self.write(csv::Terminator::Any(b'\n'))
+    }
+
+    /// Serialize like `to_csv`, keeping the line endings of `original`
+    /// (CRLF or LF) so a rewritten file does not churn on every line.
+    pub fn to_csv_like(&self, original: &str) -> Result<String, AppError> {
+        if original.contains("\r\n") {
+            self.write(csv::Terminator::CRLF)
+        } else {
+            self.to_csv()
+        }
+    }
+
+    fn write(&self, terminator: csv::Terminator) -> Result<String, AppError> {
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(self.delimiter)
+            .terminator(terminator)
+            .flexible(true)
+            .from_writer(Vec::new());
+
+        if !self.headers.is_empty() {
+            writer.write_record(&self.headers)?;
+        }
+        for row in &self.rows {
+            let mut record = row.clone();
+            if record.len() < self.headers.len() {
+                record.resize(self.headers.len(), String::new());
+            }
+            writer.write_record(&record)?;
+        }
+
+        let bytes = writer
+            .into_inner()
+            .map_err(|e| AppError::InvalidCsv(e.to_string()))?;
+        String::from_utf8(bytes).map_err(|e| AppError::InvalidCsv(e.to_string()))
+    }
+
+    /// Index of a column by header name.
+    pub fn column_index(&self, column: &str) -> Option<usize> {
+        self.headers.iter().position(|h| h == column)
+    }
+
+    /// Value of a cell, or `None` if the column does not exist in this table.
+    /// Cells missing from short rows read as empty strings.
+    pub fn cell(&self, row: usize, column: &str) -> Option<&str> {
+        let col = self.column_index(column)?;
+        let row = self.rows.get(row)?;
+        Some(row.get(col).map(|c| c.as_str()).unwrap_or(""))
+    }
+
+    /// Raw (possibly duplicated) key of a row: the values of the key columns
+    /// joined with `|`, or the 1-based row position when this table lacks
+    /// one of the key columns.
+    pub fn raw_row_key(&self, row: usize, key_columns: &[String]) -> String {
+        let values: Option<Vec<&str>> = key_columns
+            .iter()
+            .map(|column| self.cell(row, column))
+            .collect();
+        match values {
+            Some(values) if !values.is_empty() => values.join("|"),
+            _ => format!("#{}", row + 1),
+        }
+    }
+
+    /// Unique key for every row. Repeated keys get an occurrence suffix
+    /// (`key (2)`, `key (3)`, …) so that duplicate rows still line up by order.
+    /// Suffixes that would clash with a real key in the table are skipped.
+    pub fn row_keys(&self, key_columns: &[String]) -> Vec<String> {
+        let raw: Vec<String> = (0..self.rows.len())
+            .map(|row| self.raw_row_key(row, key_columns))
+            .collect();
+        let taken: HashSet<&str> = raw.iter().map(String::as_str).collect();
+        let mut seen: HashMap<&str, usize> = HashMap::new();
+        let mut keys: Vec<String> = Vec::with_capacity(raw.len());
+        for key in &raw {
+            let count = seen.entry(key.as_str()).or_insert(0);
+            *count += 1;
+            if *count == 1 {
+                keys.push(key.clone());
+                continue;
+            }
+            let mut suffixed = format!("{} ({})", key, count);
+            while taken.contains(suffixed.as_str()) {
+                *count += 1;
+                suffixed = format!("{} ({})", key, count);
+            }
+            keys.push(suffixed);
+        }
+        keys
+    }
+}
+
+/// Whether a path is a table Ledgit can diff and merge cell by cell.
+pub fn is_table_path(path: &str) -> bool {
+    let ext = Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    ext == "csv" || ext == "tsv"
+}
+
+/// Key columns to match rows by: the explicit list if given, otherwise the
+/// first column of the first table that has any columns.
+pub fn resolve_key_columns(key_columns: &[String], tables: &[&Table]) -> Vec<String> {
+    if !key_columns.is_empty() {
+        return key_columns.to_vec();
+    }
+    tables
+        .iter()
+        .find_map(|t| t.headers.first())
+        .map(|first| vec![first.clone()])
+        .unwrap_or_default()
+}
+
+/// Compute cell-level differences from `old` to `new`, matching rows by key.
+pub fn diff_tables(old: &Table, new: &Table, key_columns: &[String]) -> TableDiff {
+    let key_columns = resolve_key_columns(key_columns, &[new, old]);
+
+    let added_columns: Vec<String> = new
+        .headers
+        .iter()
+        .filter(|h| old.column_index(h).is_none())
+        .cloned()
+        .collect();
+    let removed_columns: Vec<String> = old
+        .headers
+        .iter()
+        .filter(|h| new.column_index(h).is_none())
+        .cloned()
+        .collect();
+
+    let old_keys = old.row_keys(&key_columns);
+    let new_keys = new.row_keys(&key_columns);
+    let old_index: HashMap<&str, usize> = old_keys
+        .iter()
+        .enumerate()
+        .map(|(i, k)| (k.as_str(), i))
+        .collect();
+    let new_set: HashSet<&str> = new_keys.iter().map(|k| k.as_str()).collect();
+
+    let removed_rows: Vec<String> = old_keys
+        .iter()
+        .filter(|k| !new_set.contains(k.as_str()))
+        .cloned()
+        .collect();
+
+    let mut added_rows = Vec::new();
+    let mut modified_cells = Vec::new();
+    for (new_row, key) in new_keys.iter().enumerate() {
+        let Some(&old_row) = old_index.get(key.as_str()) else {
+            added_rows.push(key.clone());
+            continue;
+        };
+        for column in &new.headers {
+            let Some(old_value) = old.cell(old_row, column) else { continue };
+            let new_value = new.cell(new_row, column).unwrap_or("");
+            if old_value != new_value {
+                modified_cells.push(CellChange {
+                    row_key: key.clone(),
+                    column: column.clone(),
+                    old_value: old_value.to_string(),
+                    new_value: new_value.to_string(),
+                });
+            }
+        }
+    }
+
+    let mut diff = TableDiff {
+        added_rows,
+        removed_rows,
+        modified_cells,
+        added_columns,
+        removed_columns,
+        summary: String::new(),
+    };
+    diff.summary = summarize(&diff);
+    diff
+}
+
+/// Three-way merge of tables at cell granularity. Rows are matched by key and
+/// columns by header name; a cell only conflicts when both sides changed it to
+/// different values. Row order follows ours, with rows added by theirs appended.
+pub fn merge_tables(
+    ancestor: &Table,
+    ours: &Table,
+    theirs: &Table,
+    key_columns: &[String],
+) -> TableMerge {
+    let key_columns = resolve_key_columns(key_columns, &[ours, theirs, ancestor]);
+
+    // Every column any side knows about, in ours → theirs → ancestor order
+    let mut all_columns: Vec<String> = Vec::new();
+    for column in ours.headers.iter().chain(&theirs.headers).chain(&ancestor.headers) {
+        if !all_columns.contains(column) {
+            all_columns.push(column.clone());
+        }
+    }
+    let headers: Vec<String> = all_columns
+        .iter()
+        .filter(|c| {
+            merge3(
+                ancestor.column_index(c).is_some(),
+                ours.column_index(c).is_some(),
+                theirs.column_index(c).is_some(),
+            )
+        })
+        .cloned()
+        .collect();
+
+    let ancestor_rows = key_index(ancestor, &key_columns);
+    let our_keys = ours.row_keys(&key_columns);
+    let our_rows: HashMap<&str, usize> = our_keys
+        .iter()
+        .enumerate()
+        .map(|(i, k)| (k.as_str(), i))
+        .collect();
+    let their_keys = theirs.row_keys(&key_columns);
+    let their_rows: HashMap<&str, usize> = their_keys
+        .iter()
+        .enumerate()
+        .map(|(i, k)| (k.as_str(), i))
+        .collect();
+
+    let order = our_keys
+        .iter()
+        .chain(their_keys.iter().filter(|k| !our_rows.contains_key(k.as_str())));
+
+    let mut rows = Vec::new();
+    let mut conflicts = Vec::new();
+
+    for key in order {
+        let a = ancestor_rows.get(key.as_str()).copied();
+        let o = our_rows.get(key.as_str()).copied();
+        let t = their_rows.get(key.as_str()).copied();
+
+        let cell = |table: &Table, row: Option<usize>, column: &str| -> Option<String> {
+            row.and_then(|r| table.cell(r, column)).map(|v| v.to_string())
+        };
+
+        let row_changed = |table: &Table, row: usize| {
+            all_columns
+                .iter()
+                .any(|c| cell(table, Some(row), c) != cell(ancestor, a, c))
+        };
+
+        match (o, t) {
+            (Some(_), Some(_)) => {}
+            // Deleted on one side: drop it unless the other side edited the row
+            (Some(row), None) | (None, Some(row)) if a.is_some() => {
+                let (table, ours_kept) = if o.is_some() { (ours, true) } else { (theirs, false) };
+                if !row_changed(table, row) {
+                    continue;
+                }
+                for column in &all_columns {
+                    let base = cell(ancestor, a, column);
+                    let value = cell(table, Some(row), column);
+                    if value != base {
+                        conflicts.push(CellConflict {
+                            row_key: key.clone(),
+                            column: column.clone(),
+                            ancestor: base,
+                            ours: if ours_kept { value.clone() } else { None },
+                            theirs: if ours_kept { None } else { value },
+                        });
+                    }
+                }
+                if !ours_kept {
+                    continue;
+                }
+            }
+            // Added on one side only
+            (Some(_), None) | (None, Some(_)) => {}
+            (None, None) => continue,
+        }
+
+        let mut row = Vec::with_capacity(headers.len());
+        for column in &all_columns {
+            let base = cell(ancestor, a, column);
+            let mine = cell(ours, o, column);
+            let other = cell(theirs, t, column);
+
+            let keep_column = headers.contains(column);
+            let value = if t.is_none() {
+                mine
+            } else if o.is_none() {
+                other
+            } else if mine == other {
+                mine
+            } else if mine == base {
+                other
+            } else if other == base {
+                mine
+            } else {
+                conflicts.push(CellConflict {
+                    row_key: key.clone(),
+                    column: column.clone(),
+                    ancestor: base,
+                    ours: mine.clone(),
+                    theirs: other,
+                });
+                mine
+            };
+
+            if keep_column {
+                row.push(value.unwrap_or_default());
+            }
+        }
+        rows.push(row);
+    }
+
+    TableMerge {
+        table: Table {
+            headers,
+            rows,
+            delimiter: ours.delimiter,
+        },
+        conflicts,
+    }
+}
+
+/// Row union of two tables: every row of ours, then the rows of theirs whose
+/// key is not already present. Rows of `ancestor` that either side deleted
+/// stay deleted. Columns only theirs has are appended.
+pub fn union_tables(
+    ancestor: &Table,
+    ours: &Table,
+    theirs: &Table,
+    key_columns: &[String],
+) -> Table {
+    let key_columns = resolve_key_columns(key_columns, &[ours, theirs, ancestor]);
+    let keys = |table: &Table| -> HashSet<String> {
+        (0..table.rows.len())
+            .map(|row| table.raw_row_key(row, &key_columns))
+            .collect()
+    };
+    let (our_keys, their_keys) = (keys(ours), keys(theirs));
+    let deleted: HashSet<String> = keys(ancestor)
+        .into_iter()
+        .filter(|key| !our_keys.contains(key) || !their_keys.contains(key))
+        .collect();
+
+    let mut headers = ours.headers.clone();
+    for column in &theirs.headers {
+        if !headers.contains(column) {
+            headers.push(column.clone());
+        }
+    }
+
+    let project = |table: &Table, row: usize| -> Vec<String> {
+        headers
+            .iter()
+            .map(|h| table.cell(row, h).unwrap_or("").to_string())
+            .collect()
+    };
+
+    let mut seen = HashSet::new();
+    let mut rows = Vec::new();
+    for row in 0..ours.rows.len() {
+        let key = ours.raw_row_key(row, &key_columns);
+        if !deleted.contains(&key) {
+            seen.insert(key);
+            rows.push(project(ours, row));
+        }
+    }
+    for row in 0..theirs.rows.len() {
+        let key = theirs.raw_row_key(row, &key_columns);
+        if !deleted.contains(&key) && seen.insert(key) {
+            rows.push(project(theirs, row));
+        }
+    }
+
+    Table {
+        headers,
+        rows,
+        delimiter: ours.delimiter,
+    }
+}
+
+/// Give `owner` the final say over `columns`: merged rows that exist in the
+/// owner's table take its values for those columns, and any conflicts in
+/// those columns are dropped.
+pub fn apply_column_owner(
+    merge: &mut TableMerge,
+    owner: &Table,
+    columns: &[String],
+    key_columns: &[String],
+) {
+    let key_columns = resolve_key_columns(key_columns, &[&merge.table, owner]);
+    let owner_rows = key_index(owner, &key_columns);
+    let merged_keys = merge.table.row_keys(&key_columns);
+    let width = merge.table.headers.len();
+
+    let owned: Vec<(usize, &String)> = columns
+        .iter()
+        .filter_map(|c| merge.table.column_index(c).map(|i| (i, c)))
+        .collect();
+
+    for (row, key) in merged_keys.iter().enumerate() {
+        let Some(&owner_row) = owner_rows.get(key) else { continue };
+        let cells = &mut merge.table.rows[row];
+        if cells.len() < width {
+            cells.resize(width, String::new());
+        }
+        for (index, column) in &owned {
+            if let Some(value) = owner.cell(owner_row, column) {
+                cells[*index] = value.to_string();
+            }
+        }
+    }
+
+    merge.conflicts.retain(|c| !columns.contains(&c.column));
+}
+
+/// Copy selected rows and cells from `source` into `target`. A picked row
+/// takes the source's values for every column both tables share, is appended
+/// when `target` lacks it, and is removed when `source` lacks it. A picked
+/// cell must exist in both tables.
+pub fn pick_cells(
+    target: &Table,
+    source: &Table,
+    rows: &[String],
+    cells: &[CellRef],
+    key_columns: &[String],
+) -> Result<Table, AppError> {
+    let key_columns = resolve_key_columns(key_columns, &[target, source]);
+    let source_rows = key_index(source, &key_columns);
+    let target_rows = key_index(target, &key_columns);
+    let width = target.headers.len();
+    let mut picked = target.clone();
+
+    // Columns both tables share, as (target index, source index)
+    let shared: Vec<(usize, usize)> = target
+        .headers
+        .iter()
+        .enumerate()
+        .filter_map(|(i, h)| source.column_index(h).map(|j| (i, j)))
+        .collect();
+    let source_value = |row: usize, col: usize| {
+        source.rows[row].get(col).cloned().unwrap_or_default()
+    };
+
+    for cell in cells {
+        let (Some(&row), Some(&source_row)) =
+            (target_rows.get(&cell.row_key), source_rows.get(&cell.row_key))
+        else {
+            return Err(AppError::RowNotFound(cell.row_key.clone()));
+        };
+        let (Some(col), Some(source_col)) =
+            (target.column_index(&cell.column), source.column_index(&cell.column))
+        else {
+            return Err(AppError::ColumnNotFound(cell.column.clone()));
+        };
+        let cells = &mut picked.rows[row];
+        if cells.len() < width {
+            cells.resize(width, String::new());
+        }
+        cells[col] = source_value(source_row, source_col);
+    }
+
+    let mut removed = HashSet::new();
+    for key in rows {
+        match (target_rows.get(key), source_rows.get(key)) {
+            (Some(&row), Some(&source_row)) => {
+                let cells = &mut picked.rows[row];
+                if cells.len() < width {
+                    cells.resize(width, String::new());
+                }
+                for &(col, source_col) in &shared {
+                    cells[col] = source_value(source_row, source_col);
+                }
+            }
+            (None, Some(&source_row)) => {
+                let mut cells = vec![String::new(); width];
+                for &(col, source_col) in &shared {
+                    cells[col] = source_value(source_row, source_col);
+                }
+                picked.rows.push(cells);
+            }
+            (Some(&row), None) => {
+                removed.insert(row);
+            }
+            (None, None) => return Err(AppError::RowNotFound(key.clone())),
+        }
+    }
+
+    if !removed.is_empty() {
+        let mut row = 0;
+        picked.rows.retain(|_| {
+            row += 1;
+            !removed.contains(&(row - 1))
+        });
+    }
+    Ok(picked)
+}
+
+/// Human-readable one-line summary of a table diff.
+pub fn summarize(diff: &TableDiff) -> String {
+    let mut parts: Vec<String> = Vec::new();
+
+    if !diff.added_rows.is_empty() {
+        parts.push(format!("{} row(s) added", diff.added_rows.len()));
+    }
+    if !diff.removed_rows.is_empty() {
+        parts.push(format!("{} row(s) removed", diff.removed_rows.len()));
+    }
+    if !diff.modified_cells.is_empty() {
+        parts.push(format!("{} cell(s) modified", diff.modified_cells.len()));
+    }
+    if !diff.added_columns.is_empty() {
+        parts.push(format!("{} column(s) added", diff.added_columns.len()));
+    }
+    if !diff.removed_columns.is_empty() {
+        parts.push(format!("{} column(s) removed", diff.removed_columns.len()));
+    }
+
+    if parts.is_empty() {
+        "No changes detected".to_string()
+    } else {
+        parts.join(", ")
+    }
+}
+
+// ── Free-standing helpers ────────────────────────────────────────────
+
+/// Classic three-way pick: if only one side changed, take that side.
+/// When both changed differently the caller decides; this returns ours.
+fn merge3<T: PartialEq>(ancestor: T, ours: T, theirs: T) -> T {
+    if ours == ancestor {
+        theirs
+    } else {
+        ours
+    }
+}
+
+/// Map from unique row key to row index.
+fn key_index(table: &Table, key_columns: &[String]) -> HashMap<String, usize> {
+    table
+        .row_keys(key_columns)
+        .into_iter()
+        .enumerate()
+        .map(|(i, k)| (k, i))
+        .collect()
+}
+
+/// Detect the delimiter from the file extension, or by sniffing the first
+/// line for semicolons vs commas (same rule as `FileService`).
+fn detect_delimiter(content: &str, file_path: &str) -> u8 {
+    let is_tsv = Path::new(file_path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.eq_ignore_ascii_case("tsv"))
+        .unwrap_or(false);
+    if is_tsv {
+        return b'\t';
+    }
+
+    let first_line = content.lines().next().unwrap_or("");
+    if first_line.matches(';').count() > first_line.matches(',').count() {
+        b';'
+    } else {
+        b','
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(content: &str) -> Table {
+        Table::parse(content, "test.csv").unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_roundtrip() {
+        let t = table("id,name\n1,\"Smith, J\"\n2,Lee\n");
+        assert_eq!(t.headers, vec!["id", "name"]);
+        assert_eq!(t.rows.len(), 2);
+        assert_eq!(t.cell(0, "name"), Some("Smith, J"));
+        assert_eq!(t.to_csv().unwrap(), "id,name\n1,\"Smith, J\"\n2,Lee\n");
+    }
+
+    #[test]
+    fn test_duplicate_keys_get_suffix() {
+        let t = table("id,v\n1,a\n1,b\n2,c\n");
+        assert_eq!(t.row_keys(&["id".to_string()]), vec!["1", "1 (2)", "2"]);
+
+        // A real key that looks like a suffix keeps its name
+        let t = table("id,v\n1,a\n1,b\n1 (2),c\n");
+        assert_eq!(t.row_keys(&["id".to_string()]), vec!["1", "1 (3)", "1 (2)"]);
+    }
+
+    #[test]
+    fn test_diff_tables() {
+        let old = table("id,name,qty\n1,apple,3\n2,pear,5\n");
+        let new = table("id,name,price\n1,apple,1.0\n2,plum,2.0\n3,fig,4.0\n");
+        let diff = diff_tables(&old, &new, &[]);
+
+        assert_eq!(diff.added_rows, vec!["3"]);
+        assert!(diff.removed_rows.is_empty());
+        assert_eq!(diff.added_columns, vec!["price"]);
+        assert_eq!(diff.removed_columns, vec!["qty"]);
+        assert_eq!(diff.modified_cells.len(), 1);
+        assert_eq!(diff.modified_cells[0].row_key, "2");
+        assert_eq!(diff.modified_cells[0].new_value, "plum");
+        assert_eq!(
+            diff.summary,
+            "1 row(s) added, 1 cell(s) modified, 1 column(s) added, 1 column(s) removed"
+        );
+    }
+
+    #[test]
+    fn test_merge_non_overlapping_cells() {
+        let base = table("id,a,b\n1,x,y\n2,x,y\n");
+        let ours = table("id,a,b\n1,OURS,y\n2,x,y\n");
+        let theirs = table("id,a,b\n1,x,THEIRS\n2,x,y\n3,new,row\n");
+        let merged = merge_tables(&base, &ours, &theirs, &[]);
+
+        assert!(merged.conflicts.is_empty());
+        assert_eq!(
+            merged.table.to_csv().unwrap(),
+            "id,a,b\n1,OURS,THEIRS\n2,x,y\n3,new,row\n"
+        );
+    }
+
+    #[test]
+    fn test_merge_column_changes() {
+        let base = table("id,a\n1,x\n");
+        let ours = table("id,a,ours_col\n1,x,o\n");
+        let theirs = table("id,a,their_col\n1,y,t\n");
+        let merged = merge_tables(&base, &ours, &theirs, &[]);
+
+        assert!(merged.conflicts.is_empty());
+        assert_eq!(
+            merged.table.to_csv().unwrap(),
+            "id,a,ours_col,their_col\n1,y,o,t\n"
+        );
+    }
+
+    #[test]
+    fn test_merge_reports_cell_conflicts() {
+        let base = table("id,a\n1,x\n2,x\n");
+        let ours = table("id,a\n1,ours\n");
+        let theirs = table("id,a\n1,theirs\n2,edited\n");
+        let merged = merge_tables(&base, &ours, &theirs, &[]);
+
+        assert_eq!(merged.conflicts.len(), 2);
+        let cell = &merged.conflicts[0];
+        assert_eq!(cell.row_key, "1");
+        assert_eq!(cell.ours.as_deref(), Some("ours"));
+        assert_eq!(cell.theirs.as_deref(), Some("theirs"));
+
+        // Row 2 was deleted by ours but edited by theirs
+        let deleted = &merged.conflicts[1];
+        assert_eq!(deleted.row_key, "2");
+        assert_eq!(deleted.ours, None);
+        assert_eq!(deleted.theirs.as_deref(), Some("edited"));
+
+        // Conflicts provisionally keep ours
+        assert_eq!(merged.table.to_csv().unwrap(), "id,a\n1,ours\n");
+    }
+
+    #[test]
+    fn test_union_dedups_by_key() {
+        let ours = table("id,event\n1,login\n2,logout\n");
+        let theirs = table("id,event,user\n1,login,amy\n3,login,bob\n");
+        let union = union_tables(&Table::empty(b','), &ours, &theirs, &[]);
+
+        assert_eq!(
+            union.to_csv().unwrap(),
+            "id,event,user\n1,login,\n2,logout,\n3,login,bob\n"
+        );
+
+        // Rows deleted on either side since the ancestor don't come back
+        let base = table("id,event\n1,login\n2,logout\n");
+        let ours = table("id,event\n2,logout\n4,login\n");
+        let theirs = table("id,event\n1,login\n5,login\n");
+        let union = union_tables(&base, &ours, &theirs, &[]);
+        assert_eq!(union.to_csv().unwrap(), "id,event\n4,login\n5,login\n");
+    }
+
+    #[test]
+    fn test_column_owner_wins() {
+        let base = table("id,price,note\n1,10,a\n");
+        let ours = table("id,price,note\n1,12,ours\n");
+        let theirs = table("id,price,note\n1,11,theirs\n");
+        let mut merged = merge_tables(&base, &ours, &theirs, &[]);
+        assert_eq!(merged.conflicts.len(), 2);
+
+        apply_column_owner(&mut merged, &theirs, &["price".to_string()], &[]);
+
+        assert_eq!(merged.conflicts.len(), 1);
+        assert_eq!(merged.conflicts[0].column, "note");
+        assert_eq!(merged.table.to_csv().unwrap(), "id,price,note\n1,11,ours\n");
+    }
+
+    #[test]
+    fn test_pick_rows_and_cells() {
+        let target = table("id,a,b,local\n1,x,y,l\n2,x,y,l\n3,x,y,l\n");
+        let source = table("id,a,b\n1,A,B\n2,A,B\n4,new,row\n");
+        let cell = CellRef {
+            row_key: "2".to_string(),
+            column: "b".to_string(),
+        };
+        let rows = ["1".to_string(), "3".to_string(), "4".to_string()];
+        let picked = pick_cells(&target, &source, &rows, &[cell], &[]).unwrap();
+
+        assert_eq!(
+            picked.to_csv().unwrap(),
+            "id,a,b,local\n1,A,B,l\n2,x,B,l\n4,new,row,\n"
+        );
+
+        let missing = CellRef {
+            row_key: "4".to_string(),
+            column: "a".to_string(),
+        };
+        assert!(matches!(
+            pick_cells(&target, &source, &[], &[missing], &[]),
+            Err(AppError::RowNotFound(key)) if key == "4"
+        ));
+    }
+}
@@ -0,0 +1,401 @@
+use std::cell::{Cell, RefCell};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use base64::Engine;
+use git2::{
+    CertificateCheckStatus, Cred, CredentialType, ErrorClass, ErrorCode, RemoteCallbacks,
+};
+
+use crate::models::error::AppError;
+use crate::models::git::{
+    PushRefStatus, RefUpdate, RemoteAuth, TransferProgress, TransferStage,
+};
+
+/// How many times the credential callback answers before giving up; libgit2
+/// keeps asking as long as it is handed credentials the server rejects.
+const MAX_CREDENTIAL_ATTEMPTS: usize = 5;
+
+/// Private keys tried, in order, when no key file is given and the agent fails.
+const DEFAULT_SSH_KEYS: [&str; 3] = ["id_ed25519", "id_ecdsa", "id_rsa"];
+
+/// State shared by the callbacks of one clone, fetch or push: credentials,
+/// progress reporting, cancellation, and what went wrong for error mapping.
+pub struct Transfer<'a> {
+    url: String,
+    auth: &'a RemoteAuth,
+    cancel: &'a AtomicBool,
+    progress: RefCell<&'a mut (dyn FnMut(TransferProgress) + 'a)>,
+    credential_attempts: Cell<usize>,
+    ssh_key_attempts: Cell<usize>,
+    /// Key file last offered without a passphrase.
+    unlocked_key: RefCell<Option<PathBuf>>,
+    /// Host and fingerprint of the last SSH host key seen.
+    host_key: RefCell<Option<(String, String)>>,
+    /// Per-ref outcome reported by the remote during a push.
+    pushed: RefCell<Vec<PushRefStatus>>,
+    /// Local refs moved by a fetch.
+    updated: RefCell<Vec<RefUpdate>>,
+}
+
+impl<'a> Transfer<'a> {
+    pub fn new(
+        url: &str,
+        auth: &'a RemoteAuth,
+        cancel: &'a AtomicBool,
+        progress: &'a mut (dyn FnMut(TransferProgress) + 'a),
+    ) -> Self {
+        Self {
+            url: url.to_string(),
+            auth,
+            cancel,
+            progress: RefCell::new(progress),
+            credential_attempts: Cell::new(0),
+            ssh_key_attempts: Cell::new(0),
+            unlocked_key: RefCell::new(None),
+            host_key: RefCell::new(None),
+            pushed: RefCell::new(Vec::new()),
+            updated: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Remote callbacks for credentials, host-key checking, progress and
+    /// cancellation. Each connection starts over with the first credential.
+    pub fn callbacks(&self) -> RemoteCallbacks<'_> {
+        self.credential_attempts.set(0);
+        self.ssh_key_attempts.set(0);
+        let mut callbacks = RemoteCallbacks::new();
+        callbacks.transfer_progress(|stats| {
+            let stage = if stats.received_objects() < stats.total_objects() {
+                TransferStage::Receiving
+            } else {
+                TransferStage::Resolving
+            };
+            self.report(TransferProgress {
+                stage,
+                received_objects: stats.received_objects(),
+                indexed_objects: stats.indexed_objects(),
+                total_objects: stats.total_objects(),
+                indexed_deltas: stats.indexed_deltas(),
+                total_deltas: stats.total_deltas(),
+                received_bytes: stats.received_bytes(),
+                ..Default::default()
+            });
+            !self.is_cancelled()
+        });
+        callbacks.push_transfer_progress(|current, total, bytes| {
+            self.report(TransferProgress {
+                stage: TransferStage::Sending,
+                total_objects: total,
+                sent_objects: current,
+                sent_bytes: bytes,
+                ..Default::default()
+            });
+        });
+        callbacks.credentials(|url, username, allowed| self.credentials(url, username, allowed));
+        callbacks.certificate_check(|cert, host| {
+            let Some(sha256) = cert.as_hostkey().and_then(|key| key.hash_sha256()) else {
+                return Ok(CertificateCheckStatus::CertificatePassthrough);
+            };
+            let fingerprint = fingerprint(sha256);
+            let trusted = self.auth.trusted_host_keys.contains(&fingerprint);
+            *self.host_key.borrow_mut() = Some((host.to_string(), fingerprint));
+            // Hosts not explicitly trusted are checked against known_hosts
+            Ok(if trusted {
+                CertificateCheckStatus::CertificateOk
+            } else {
+                CertificateCheckStatus::CertificatePassthrough
+            })
+        });
+        callbacks.update_tips(|refname, old, new| {
+            let oid = |oid: git2::Oid| (!oid.is_zero()).then(|| oid.to_string());
+            self.updated.borrow_mut().push(RefUpdate {
+                name: refname.to_string(),
+                old: oid(old),
+                new: oid(new),
+            });
+            true
+        });
+        callbacks.push_update_reference(|refname, status| {
+            self.pushed.borrow_mut().push(PushRefStatus {
+                name: refname.to_string(),
+                ok: status.is_none(),
+                message: status.map(str::to_string),
+            });
+            // Cancelling after the upload leaves the remote as the server decided
+            if self.is_cancelled() {
+                return Err(git2::Error::from_str("push cancelled"));
+            }
+            Ok(())
+        });
+        callbacks
+    }
+
+    /// Send a progress update.
+    pub fn report(&self, progress: TransferProgress) {
+        (self.progress.borrow_mut())(progress);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+
+    /// Per-ref outcome of a push.
+    pub fn pushed(&self) -> Vec<PushRefStatus> {
+        self.pushed.borrow().clone()
+    }
+
+    /// Refs moved by a fetch.
+    pub fn updated(&self) -> Vec<RefUpdate> {
+        self.updated.borrow().clone()
+    }
+
+    /// Turn a libgit2 error from this transfer into a specific `AppError`.
+    pub fn map_error(&self, err: git2::Error) -> AppError {
+        if self.is_cancelled() {
+            return AppError::Cancelled;
+        }
+
+        let message = err.message().to_lowercase();
+        if let Some(key) = self.unlocked_key.borrow().as_ref() {
+            if err.class() == ErrorClass::Ssh && message.contains("private key") {
+                return AppError::PassphraseRequired(key.to_string_lossy().to_string());
+            }
+        }
+
+        match (err.code(), err.class()) {
+            (ErrorCode::Certificate, _) => match self.host_key.borrow().clone() {
+                Some((host, fingerprint)) => AppError::UnknownHostKey { host, fingerprint },
+                None => AppError::GitError(err),
+            },
+            (ErrorCode::Auth, _) => AppError::AuthFailed(self.url.clone()),
+            (ErrorCode::NotFastForward, _) => AppError::PushRejected {
+                remote: self.url.clone(),
+                refs: self
+                    .pushed()
+                    .into_iter()
+                    .filter(|status| !status.ok)
+                    .map(|status| status.name)
+                    .collect(),
+            },
+            (_, ErrorClass::Net) => AppError::HostUnreachable(self.url.clone()),
+            (_, ErrorClass::Ssh | ErrorClass::Http)
+                if ["authentication", "401", "403", "credentials"]
+                    .iter()
+                    .any(|m| message.contains(m)) =>
+            {
+                AppError::AuthFailed(self.url.clone())
+            }
+            // Socket failures are reported as OS errors too
+            (_, ErrorClass::Ssh | ErrorClass::Os)
+                if ["connect", "resolve", "timed out"].iter().any(|m| message.contains(m)) =>
+            {
+                AppError::HostUnreachable(self.url.clone())
+            }
+            // Anything else from the OS is local: permissions, a full disk, …
+            (_, ErrorClass::Os) => {
+                AppError::IoError(std::io::Error::other(err.message().to_string()))
+            }
+            _ => AppError::GitError(err),
+        }
+    }
+
+    /// Credentials in order of preference: the configured key file or the
+    /// ssh-agent followed by default key files for SSH, and the configured
+    /// password/token or the git credential helper for HTTPS.
+    fn credentials(
+        &self,
+        url: &str,
+        username: Option<&str>,
+        allowed: CredentialType,
+    ) -> Result<Cred, git2::Error> {
+        let attempt = self.credential_attempts.get();
+        self.credential_attempts.set(attempt + 1);
+        if attempt >= MAX_CREDENTIAL_ATTEMPTS {
+            return Err(auth_error("no more credentials to try"));
+        }
+
+        let username = self
+            .auth
+            .username
+            .as_deref()
+            .or(username)
+            .unwrap_or("git");
+
+        if allowed.contains(CredentialType::USERNAME) {
+            return Cred::username(username);
+        }
+
+        if allowed.contains(CredentialType::SSH_KEY) {
+            let key_attempt = self.ssh_key_attempts.get();
+            self.ssh_key_attempts.set(key_attempt + 1);
+
+            if let Some(path) = &self.auth.ssh_key_path {
+                if key_attempt > 0 {
+                    return Err(auth_error("SSH key was rejected"));
+                }
+                return self.key_file(username, Path::new(path));
+            }
+            if key_attempt == 0 {
+                return Cred::ssh_key_from_agent(username);
+            }
+            return match default_ssh_keys().into_iter().nth(key_attempt - 1) {
+                Some(path) => self.key_file(username, &path),
+                None => Err(auth_error("no SSH key was accepted")),
+            };
+        }
+
+        if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+            if let Some(password) = &self.auth.password {
+                return Cred::userpass_plaintext(username, password);
+            }
+            let config = git2::Config::open_default()?;
+            return Cred::credential_helper(&config, url, self.auth.username.as_deref());
+        }
+
+        Cred::default()
+    }
+
+    fn key_file(&self, username: &str, path: &Path) -> Result<Cred, git2::Error> {
+        let passphrase = self.auth.passphrase.as_deref();
+        *self.unlocked_key.borrow_mut() = passphrase.is_none().then(|| path.to_path_buf());
+        Cred::ssh_key(username, None, path, passphrase)
+    }
+}
+
+/// Progress update for the checkout that follows a clone.
+pub fn checkout_progress(checked_out: usize, checkout_total: usize) -> TransferProgress {
+    TransferProgress {
+        stage: TransferStage::CheckingOut,
+        checked_out,
+        checkout_total,
+        ..Default::default()
+    }
+}
+
+/// Whether `url` refers to a repository on the local filesystem, which
+/// libgit2 fetches through its local transport.
+pub fn is_local(url: &str) -> bool {
+    url.starts_with("file://") || Path::new(url).exists()
+}
+
+/// OpenSSH-style fingerprint of a host key hash, e.g. `SHA256:nThbg6kX...`.
+fn fingerprint(sha256: &[u8]) -> String {
+    format!(
+        "SHA256:{}",
+        base64::engine::general_purpose::STANDARD_NO_PAD.encode(sha256)
+    )
+}
+
+/// Existing private keys in `~/.ssh`.
+fn default_ssh_keys() -> Vec<PathBuf> {
+    let Some(home) = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE")) else {
+        return Vec::new();
+    };
+    let dir = Path::new(&home).join(".ssh");
+    DEFAULT_SSH_KEYS
+        .iter()
+        .map(|name| dir.join(name))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+fn auth_error(message: &str) -> git2::Error {
+    git2::Error::new(ErrorCode::Auth, ErrorClass::Callback, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(auth: &RemoteAuth, err: git2::Error) -> AppError {
+        let cancel = AtomicBool::new(false);
+        let mut progress = |_| {};
+        let transfer = Transfer::new("ssh://git@example.com/data.git", auth, &cancel, &mut progress);
+        transfer.map_error(err)
+    }
+
+    #[test]
+    fn test_is_local() {
+        assert!(is_local("file:///srv/data.git"));
+        assert!(is_local(env!("CARGO_MANIFEST_DIR")));
+        assert!(!is_local("https://example.com/data.git"));
+        assert!(!is_local("git@example.com:team/data.git"));
+    }
+
+    #[test]
+    fn test_fingerprint_matches_openssh_format() {
+        assert_eq!(
+            fingerprint(&[0u8; 32]),
+            "SHA256:AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA"
+        );
+    }
+
+    #[test]
+    fn test_credentials_start_over_per_connection() {
+        let auth = RemoteAuth {
+            ssh_key_path: Some("/nonexistent/id_ed25519".to_string()),
+            ..Default::default()
+        };
+        let cancel = AtomicBool::new(false);
+        let mut progress = |_| {};
+        let url = "ssh://git@example.com/data.git";
+        let transfer = Transfer::new(url, &auth, &cancel, &mut progress);
+        let offer = || transfer.credentials(url, None, CredentialType::SSH_KEY);
+
+        // A lease check connects first, then the push connects again
+        drop(transfer.callbacks());
+        assert!(offer().is_ok());
+        assert!(offer().is_err());
+        drop(transfer.callbacks());
+        assert!(offer().is_ok());
+        assert_eq!(transfer.credential_attempts.get(), 1);
+    }
+
+    #[test]
+    fn test_map_error_distinguishes_failures() {
+        let auth = RemoteAuth::default();
+        let err = |code, class, msg| git2::Error::new(code, class, msg);
+
+        assert!(matches!(
+            map(&auth, err(ErrorCode::Auth, ErrorClass::Callback, "no more credentials")),
+            AppError::AuthFailed(_)
+        ));
+        assert!(matches!(
+            map(&auth, err(ErrorCode::GenericError, ErrorClass::Http, "unexpected http status code: 401")),
+            AppError::AuthFailed(_)
+        ));
+        assert!(matches!(
+            map(&auth, err(ErrorCode::GenericError, ErrorClass::Net, "failed to resolve address")),
+            AppError::HostUnreachable(_)
+        ));
+        assert!(matches!(
+            map(&auth, err(ErrorCode::GenericError, ErrorClass::Os, "failed to connect to 127.0.0.1: Connection refused")),
+            AppError::HostUnreachable(_)
+        ));
+        assert!(matches!(
+            map(&auth, err(ErrorCode::GenericError, ErrorClass::Os, "failed to write pack: Permission denied")),
+            AppError::IoError(_)
+        ));
+        assert!(matches!(
+            map(&auth, err(ErrorCode::NotFastForward, ErrorClass::Reference, "cannot push")),
+            AppError::PushRejected { .. }
+        ));
+        assert!(matches!(
+            map(&auth, err(ErrorCode::GenericError, ErrorClass::Index, "bad index")),
+            AppError::GitError(_)
+        ));
+    }
+
+    #[test]
+    fn test_cancelled_overrides_error() {
+        let auth = RemoteAuth::default();
+        let cancel = AtomicBool::new(true);
+        let mut progress = |_| {};
+        let transfer = Transfer::new("file:///x", &auth, &cancel, &mut progress);
+        assert!(matches!(
+            transfer.map_error(git2::Error::from_str("aborted")),
+            AppError::Cancelled
+        ));
+    }
+}
@@ -0,0 +1,68 @@
+/// Match a repository-relative path against a glob pattern.
+///
+/// Follows `.gitattributes` conventions: a pattern without a `/` matches the
+/// file name in any directory, otherwise it is matched against the whole path.
+/// `*` and `?` never cross a `/`; `**` matches any number of directories.
+pub fn matches(pattern: &str, path: &str) -> bool {
+    let pattern = pattern.trim_start_matches('/');
+    if pattern.contains('/') {
+        match_bytes(pattern.as_bytes(), path.as_bytes())
+    } else {
+        let name = path.rsplit('/').next().unwrap_or(path);
+        match_bytes(pattern.as_bytes(), name.as_bytes())
+    }
+}
+
+fn match_bytes(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some(b'*') if pattern.get(1) == Some(&b'*') => {
+            // `**/` may also match zero directories
+            let rest = &pattern[2..];
+            let rest = rest.strip_prefix(b"/").unwrap_or(rest);
+            (0..=text.len()).any(|i| match_bytes(rest, &text[i..]))
+        }
+        Some(b'*') => {
+            let rest = &pattern[1..];
+            for i in 0..=text.len() {
+                if match_bytes(rest, &text[i..]) {
+                    return true;
+                }
+                if text.get(i) == Some(&b'/') {
+                    break;
+                }
+            }
+            false
+        }
+        Some(b'?') => {
+            matches!(text.first(), Some(c) if *c != b'/') && match_bytes(&pattern[1..], &text[1..])
+        }
+        Some(c) => text.first() == Some(c) && match_bytes(&pattern[1..], &text[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_name_only_pattern() {
+        assert!(matches("*.csv", "sales.csv"));
+        assert!(matches("*.csv", "data/2024/sales.csv"));
+        assert!(!matches("*.csv", "sales.tsv"));
+    }
+
+    #[test]
+    fn test_path_pattern() {
+        assert!(matches("logs/*.csv", "logs/audit.csv"));
+        assert!(!matches("logs/*.csv", "logs/2024/audit.csv"));
+        assert!(!matches("logs/*.csv", "other/logs/audit.csv"));
+    }
+
+    #[test]
+    fn test_double_star() {
+        assert!(matches("logs/**/*.csv", "logs/audit.csv"));
+        assert!(matches("logs/**/*.csv", "logs/2024/q1/audit.csv"));
+        assert!(matches("**/ref_?.csv", "a/b/ref_1.csv"));
+    }
+}
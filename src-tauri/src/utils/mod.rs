@@ -1 +1,2 @@
 pub mod commit_message;
+pub mod glob;